@@ -1,9 +1,12 @@
-use clap::Parser;
+use clap::{Parser, Subcommand, ValueEnum};
 
 /// pdoro
 #[derive(Debug, Parser)]
 #[command(author, about, long_about = None)]
 pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     /// time duration of session
     #[clap(long, short)]
     pub time: Option<String>,
@@ -35,4 +38,391 @@ pub struct Args {
     /// check if counter is running
     #[clap(long, short)]
     pub is_counter_running: bool,
+
+    /// with --is-counter-running, check if a finished pomodoro waits for `pdoro ack`
+    #[clap(long, requires = "is_counter_running")]
+    pub awaiting_ack: bool,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// start pomodoro counter
+    Start {
+        /// time duration of session
+        #[clap(required_unless_present = "until", conflicts_with = "until")]
+        time: Option<String>,
+
+        /// end the session at this local time (HH:MM)
+        #[clap(long)]
+        until: Option<String>,
+
+        /// end tomorrow instead of failing when the end time already passed today
+        #[clap(long, requires = "until")]
+        roll_over: bool,
+
+        /// config profile to take settings from
+        #[clap(long)]
+        profile: Option<String>,
+
+        /// start a break instead of a work session
+        #[clap(long = "break")]
+        is_break: bool,
+
+        /// keep counting up after the session ends until acknowledged
+        #[clap(long)]
+        overtime: bool,
+
+        /// wait for acknowledgement once finished, rerunning the callback at this interval
+        #[clap(long)]
+        remind_every: Option<String>,
+
+        /// stop reminding after this many reminders
+        #[clap(long)]
+        max_reminders: Option<u32>,
+
+        /// warn when this much time is left, can be repeated
+        #[clap(long = "warn")]
+        warnings: Vec<String>,
+
+        /// start even outside working hours or in a do-not-disturb window
+        #[clap(long)]
+        force: bool,
+
+        /// what the session is for
+        #[clap(long)]
+        task: Option<String>,
+
+        /// free form tag, can be repeated
+        #[clap(long = "tag")]
+        tags: Vec<String>,
+
+        /// callback program with args
+        #[clap(long, short)]
+        callback_with_args: Option<String>,
+    },
+
+    /// start a stopwatch counting up from zero, stop it with --halt-counter
+    Stopwatch {
+        /// mark a milestone every time this duration elapses
+        #[clap(long)]
+        every: Option<String>,
+
+        /// start even outside working hours or in a do-not-disturb window
+        #[clap(long)]
+        force: bool,
+
+        /// what the session is for
+        #[clap(long)]
+        task: Option<String>,
+
+        /// free form tag, can be repeated
+        #[clap(long = "tag")]
+        tags: Vec<String>,
+
+        /// callback program with args, run at every milestone
+        #[clap(long, short, requires = "every")]
+        callback_with_args: Option<String>,
+    },
+
+    /// pause pomodoro counter, does nothing if already paused
+    Pause {
+        /// resume automatically after this duration
+        #[clap(long = "for")]
+        resume_after: Option<String>,
+
+        /// halt the counter if the pause lasts longer than this duration
+        #[clap(long = "max")]
+        halt_after: Option<String>,
+    },
+
+    /// resume pomodoro counter, does nothing if already running
+    Resume,
+
+    /// acknowledge a finished session, ending its overtime
+    Ack,
+
+    /// add time to the current pomodoro, counting down again once it ended
+    Extend {
+        #[clap(default_value = "5m")]
+        time: String,
+    },
+
+    /// open a dashboard with the timer, today's sessions, the queue and the daily goal
+    Tui {
+        /// callback program with args, for pomodoros started or queued from the dashboard
+        #[clap(long, short)]
+        callback_with_args: Option<String>,
+    },
+
+    /// show a live countdown, space pauses, q halts and + extends the pomodoro
+    Watch {
+        /// time + adds to the pomodoro
+        #[clap(long, default_value = "5m")]
+        extend: String,
+
+        /// text with {variables}, as for `pdoro status`
+        #[clap(long)]
+        template: Option<String>,
+    },
+
+    /// log an interruption of the running session
+    Interrupt {
+        /// what interrupted
+        note: Option<String>,
+
+        /// someone else interrupted, the default
+        #[clap(long, conflicts_with = "internal")]
+        external: bool,
+
+        /// you interrupted yourself
+        #[clap(long)]
+        internal: bool,
+
+        /// pause the session as well
+        #[clap(long)]
+        pause: bool,
+    },
+
+    /// add a note on what got done to the current or last session
+    Note {
+        #[clap(required_unless_present_any = ["prompt", "edit"])]
+        text: Option<String>,
+
+        /// ask for a one line summary on the terminal
+        #[clap(long, conflicts_with_all = ["text", "edit"])]
+        prompt: bool,
+
+        /// write the note in $VISUAL or $EDITOR
+        #[clap(long, conflicts_with = "text")]
+        edit: bool,
+    },
+
+    /// show progress towards today's goal
+    Goal,
+
+    /// print the timer for a status bar
+    Status {
+        #[clap(long, value_enum, default_value = "plain")]
+        format: StatusFormat,
+
+        /// text with {variables}, e.g. '{phase} {mm}:{ss} [{task}] {progress_bar:10}'
+        #[clap(long)]
+        template: Option<String>,
+    },
+
+    /// block until the timer completes or is halted, exiting 0 when it did what was waited for,
+    /// 2 when it ended the other way and 3 on timeout
+    Wait {
+        /// give up after this long
+        #[clap(long)]
+        timeout: Option<String>,
+
+        #[clap(long = "for", value_enum, default_value = "complete")]
+        until: WaitFor,
+    },
+
+    /// print the timer for a shell prompt, from the file the server keeps it in
+    Prompt {
+        /// text with {variables}, as for `status`
+        #[clap(long)]
+        template: Option<String>,
+    },
+
+    /// summarize recorded sessions
+    Report {
+        #[command(subcommand)]
+        command: ReportCommand,
+    },
+
+    /// print recorded sessions for other tools
+    Export {
+        #[clap(long, value_enum, default_value = "csv")]
+        format: ExportFormat,
+
+        /// first day to export (YYYY-MM-DD)
+        #[clap(long)]
+        from: Option<String>,
+
+        /// last day to export (YYYY-MM-DD)
+        #[clap(long)]
+        to: Option<String>,
+    },
+
+    /// add sessions exported by pdoro or another tool to history
+    Import {
+        /// file to import, - for stdin
+        path: String,
+
+        #[clap(long, value_enum)]
+        format: ImportFormat,
+
+        /// map a csv column to start, end, duration, task or tags
+        /// (key=Column, A+B joins columns), can be repeated
+        #[clap(long = "column")]
+        columns: Vec<String>,
+
+        /// show how many sessions would be imported without importing them
+        #[clap(long)]
+        dry_run: bool,
+    },
+
+    /// manage pomodoros to run one after the other
+    Queue {
+        #[command(subcommand)]
+        command: QueueCommand,
+    },
+
+    /// show pomodoros the server starts on a schedule
+    Schedule {
+        #[command(subcommand)]
+        command: ScheduleCommand,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum QueueCommand {
+    /// add a pomodoro to the end of the queue
+    Add {
+        /// time duration of session
+        time: String,
+
+        /// what the session is for
+        task: Option<String>,
+
+        /// free form tag, can be repeated
+        #[clap(long = "tag")]
+        tags: Vec<String>,
+
+        /// config profile to take settings from
+        #[clap(long)]
+        profile: Option<String>,
+
+        /// callback program with args
+        #[clap(long, short)]
+        callback_with_args: Option<String>,
+    },
+
+    /// list queued pomodoros
+    List,
+
+    /// remove the pomodoro at a position
+    Remove { position: usize },
+
+    /// move the pomodoro at a position to another one
+    Move { from: usize, to: usize },
+
+    /// remove every queued pomodoro
+    Clear,
+
+    /// start the next queued pomodoro now
+    Next {
+        /// start even outside working hours or in a do-not-disturb window
+        #[clap(long)]
+        force: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ScheduleCommand {
+    /// list the next run of every configured schedule
+    List,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ReportCommand {
+    /// list the sessions of a day with their notes
+    Sessions {
+        /// day to report on (YYYY-MM-DD), defaults to today
+        #[clap(long)]
+        date: Option<String>,
+    },
+
+    /// list the interruptions of a day
+    Interruptions {
+        /// day to report on (YYYY-MM-DD), defaults to today
+        #[clap(long)]
+        date: Option<String>,
+    },
+
+    /// focused time per task or tag and day
+    Timesheet {
+        /// report on the current week, the default
+        #[clap(long, conflicts_with = "from")]
+        week: bool,
+
+        /// first day to report on (YYYY-MM-DD)
+        #[clap(long, requires = "to")]
+        from: Option<String>,
+
+        /// last day to report on (YYYY-MM-DD)
+        #[clap(long, requires = "from")]
+        to: Option<String>,
+
+        #[clap(long, value_enum, default_value = "task")]
+        by: GroupBy,
+
+        /// round every cell to this increment, overrides the config
+        #[clap(long)]
+        round: Option<String>,
+
+        #[clap(long, value_enum, default_value = "text")]
+        format: TimesheetFormat,
+    },
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum GroupBy {
+    Task,
+    /// sessions count towards each of their tags
+    Tag,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum TimesheetFormat {
+    Text,
+    Markdown,
+    /// hours with two decimals
+    Csv,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum WaitFor {
+    /// the pomodoro reaches zero, overtime and acknowledgement are not waited for
+    Complete,
+    Halt,
+    /// either of them
+    Any,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum StatusFormat {
+    /// what `pdoro -r` prints
+    Plain,
+    /// json for a custom module with `return-type = "json"`
+    Waybar,
+    I3blocks,
+    Polybar,
+    Tmux,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+    /// iCalendar, one event per session
+    Ics,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum ImportFormat {
+    /// pdoro export or history
+    Json,
+    /// pdoro export
+    Csv,
+    /// Toggl Track detailed report
+    Toggl,
+    /// Clockify detailed report
+    Clockify,
+    /// any csv, mapped with --column
+    Columns,
 }
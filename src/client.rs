@@ -25,7 +25,7 @@ impl Client {
     pub fn run(&self, request_line: &str) -> Result<Response, ClientError> {
         match TcpStream::connect(&self.addr) {
             Ok(mut stream) => {
-                if let Err(_) = stream.write(request_line.as_bytes()) {
+                if stream.write_all(request_line.as_bytes()).is_err() {
                     return Err(ClientError::WriteError);
                 }
 
@@ -42,4 +42,12 @@ impl Client {
 }
 
 pub mod actions;
+pub mod export;
+pub mod import;
+pub mod reports;
 pub mod response;
+pub mod status;
+pub mod template;
+pub mod tui;
+pub mod wait;
+pub mod watch;
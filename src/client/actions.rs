@@ -1,11 +1,20 @@
-use std::fs::File;
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::process::{self, Command};
 
 use daemonize::Daemonize;
 
-use crate::client::{response::Response, Client};
+use crate::args::StatusFormat;
+use crate::client::{response::Response, status, Client};
+use crate::config::{Config, Profile};
+use crate::header::escape;
+use crate::history::Source;
+use crate::labels::Labels;
+use crate::phase::Phase;
 use crate::server::tcp_handler::TCPHandler;
-use crate::server::Server;
-use crate::time::Time;
+use crate::server::{queue, scheduler, status_file, Server};
+use crate::time::{Bounds, Time};
 use crate::utils::{stderr, stdout};
 
 use super::ClientError;
@@ -24,42 +33,87 @@ impl HandledRun for Client {
     }
 }
 
-static IP: &'static str = "127.0.0.1:51789";
+pub static IP: &str = "127.0.0.1:51789";
 
 pub fn remaining() {
-    Client::new(IP).safe_run("remaining;", |res| {
-        let digits = res.valid_msg().expect("Failed to retrieve remaining time.");
-
-        let seconds = digits
-            .parse::<u32>()
-            .expect("Failed to parse remaining time.");
-
-        let clock = Time::get_clock_from_seconds(&seconds);
+    status::status(StatusFormat::Plain, None)
+}
 
-        match (clock.as_str(), res.status()) {
-            ("00", _) => stdout("No pomodoro timer is running."),
-            (_, 304) => stdout(format!("{} (paused)", &clock).as_str()),
-            _ => stdout(&clock),
-        }
+pub fn goal() {
+    Client::new(IP).safe_run("goal;", |res| match res.status() {
+        200 => stdout(res.msg()),
+        _ => stderr(res.msg()),
     });
 }
 
-pub fn is_counter_running() {
-    Client::new(IP).safe_run("is-counter-running;", |res| match res.status() {
+pub fn is_counter_running(awaiting_ack: bool) {
+    let request = match awaiting_ack {
+        true => "is-counter-running awaiting-ack;",
+        false => "is-counter-running;",
+    };
+
+    Client::new(IP).safe_run(request, |res| match res.status() {
         100 | 102 => stdout(res.msg()),
         _ => stderr(res.msg()),
     });
 }
 
 pub fn is_valid_time(input: &str) {
-    match Time::new(&input) {
+    let profile = match get_profile(None) {
+        Ok(profile) => profile,
+        Err(e) => return stderr(e.as_str()),
+    };
+
+    match Time::new(input, profile.bounds(Phase::Work)) {
         Ok(_) => stdout("true"),
         Err(_) => stdout("false"),
     }
 }
 
-pub fn start(time: &str, callback_with_args: &str) {
-    let start_request = match get_start_request(time, callback_with_args) {
+pub struct StartOptions {
+    pub profile: Option<String>,
+    pub phase: Phase,
+    pub overtime: bool,
+    pub remind_every: Option<String>,
+    pub max_reminders: Option<u32>,
+    pub warnings: Vec<String>,
+    pub force: bool,
+    pub labels: Labels,
+    pub callback_with_args: Option<String>,
+}
+
+pub fn start(time: &str, options: &StartOptions) {
+    let profile = match get_profile(options.profile.as_deref()) {
+        Ok(profile) => profile,
+        Err(e) => return stderr(e.as_str()),
+    };
+
+    let time = Time::new(time, profile.bounds(options.phase)).map_err(|e| e.to_string());
+
+    send_start_request(time, &profile, options)
+}
+
+/// Request starting a pomodoro of `time` with `options`.
+pub fn start_request(time: &str, options: &StartOptions) -> Result<String, String> {
+    let profile = get_profile(options.profile.as_deref())?;
+    let time = Time::new(time, profile.bounds(options.phase)).map_err(|e| e.to_string())?;
+
+    get_start_request(&time, &profile, options)
+}
+
+pub fn start_until(end_time: &str, roll_over: bool, options: &StartOptions) {
+    let profile = match get_profile(options.profile.as_deref()) {
+        Ok(profile) => profile,
+        Err(e) => return stderr(e.as_str()),
+    };
+
+    let time = Time::until(end_time, roll_over, profile.bounds(options.phase));
+
+    send_start_request(time, &profile, options)
+}
+
+fn send_start_request(time: Result<Time, String>, profile: &Profile, options: &StartOptions) {
+    let start_request = match time.and_then(|time| get_start_request(&time, profile, options)) {
         Ok(req) => req,
         Err(e) => return stderr(e.as_str()),
     };
@@ -70,6 +124,204 @@ pub fn start(time: &str, callback_with_args: &str) {
     });
 }
 
+fn get_start_request(
+    time: &Time,
+    profile: &Profile,
+    options: &StartOptions,
+) -> Result<String, String> {
+    let mut headers = String::new();
+
+    if options.overtime || profile.overtime {
+        headers.push_str("\novertime: true");
+    }
+
+    let remind_every = match &options.remind_every {
+        Some(every) => Some(
+            Time::new(every, &Bounds::default())
+                .map_err(|e| e.to_string())?
+                .get_seconds(),
+        ),
+        None => profile.remind_every,
+    };
+
+    if let Some(every) = remind_every {
+        headers.push_str(format!("\nremind-every: {}", every).as_str());
+    }
+
+    if let Some(max) = options.max_reminders.or(profile.max_reminders) {
+        headers.push_str(format!("\nmax-reminders: {}", max).as_str());
+    }
+
+    let warnings = match options.warnings.is_empty() {
+        true => profile.warnings.clone(),
+        false => options
+            .warnings
+            .iter()
+            .map(|w| Time::new(w, &Bounds::default()).map(|t| t.get_seconds()))
+            .collect::<Result<_, _>>()
+            .map_err(|e| e.to_string())?,
+    };
+
+    if !warnings.is_empty() {
+        let warnings: Vec<String> = warnings.iter().map(|w| w.to_string()).collect();
+        headers.push_str(format!("\nwarnings: {}", warnings.join(",")).as_str());
+    }
+
+    if let Some(warning_callback) = &profile.warning_callback {
+        headers.push_str(format!("\nwarning-callback: {}", escape(warning_callback)).as_str());
+    }
+
+    if options.force {
+        headers.push_str("\nforce: true");
+    }
+
+    if let Some(profile) = &options.profile {
+        headers.push_str(format!("\nprofile: {}", escape(profile)).as_str());
+    }
+
+    if let Phase::Break = options.phase {
+        headers.push_str("\nphase: break");
+    }
+
+    headers.push_str(options.labels.headers().as_str());
+
+    Ok(format!(
+        "start {} {}{};",
+        time.get_seconds(),
+        // an empty callback runs nothing
+        options
+            .callback_with_args
+            .as_deref()
+            .map(escape)
+            .unwrap_or_default(),
+        headers
+    ))
+}
+
+pub fn start_stopwatch(
+    every: Option<&str>,
+    force: bool,
+    labels: &Labels,
+    callback_with_args: Option<&str>,
+) {
+    let milestone = match every.map(|e| Time::new(e, &Bounds::default())) {
+        Some(Ok(time)) => time.get_seconds(),
+        Some(Err(e)) => return stderr(e.to_string().as_str()),
+        None => 0,
+    };
+
+    let mut headers = labels.headers();
+
+    if force {
+        headers.push_str("\nforce: true");
+    }
+
+    let stopwatch_request = match callback_with_args {
+        Some(callback_with_args) => {
+            let callback_with_args = escape(callback_with_args);
+            format!("stopwatch {} {}{};", milestone, callback_with_args, headers)
+        }
+        None => format!("stopwatch {}{};", milestone, headers),
+    };
+
+    Client::new(IP).safe_run(stopwatch_request.as_str(), |res| match res.status() {
+        201 => stdout(res.msg()),
+        _ => stderr(res.msg()),
+    });
+}
+
+pub fn queue_add(
+    time: &str,
+    labels: &Labels,
+    profile: Option<&str>,
+    callback_with_args: Option<&str>,
+) {
+    let queue_request = match queue_add_request(time, labels, profile, callback_with_args) {
+        Ok(req) => req,
+        Err(e) => return stderr(e.as_str()),
+    };
+
+    Client::new(IP).safe_run(queue_request.as_str(), |res| match res.status() {
+        201 => stdout(res.msg()),
+        _ => stderr(res.msg()),
+    });
+}
+
+pub fn queue_add_request(
+    time: &str,
+    labels: &Labels,
+    profile: Option<&str>,
+    callback_with_args: Option<&str>,
+) -> Result<String, String> {
+    let seconds = get_profile(profile)
+        .and_then(|p| Time::new(time, p.bounds(Phase::Work)).map_err(|e| e.to_string()))?
+        .get_seconds();
+
+    let mut queue_request = format!("queue-add {}{}", seconds, labels.headers());
+
+    if let Some(profile) = profile {
+        queue_request.push_str(format!("\nprofile: {}", escape(profile)).as_str());
+    }
+
+    if let Some(callback_with_args) = callback_with_args {
+        queue_request.push_str(format!("\ncallback: {}", escape(callback_with_args)).as_str());
+    }
+
+    queue_request.push(';');
+    Ok(queue_request)
+}
+
+pub fn queue_list() {
+    Client::new(IP).safe_run("queue-list;", |res| match res.status() {
+        200 => stdout(res.msg()),
+        _ => stderr(res.msg()),
+    });
+}
+
+pub fn queue_remove(position: usize) {
+    let remove_request = format!("queue-remove {};", position);
+
+    Client::new(IP).safe_run(remove_request.as_str(), |res| match res.status() {
+        200 => stdout(res.msg()),
+        _ => stderr(res.msg()),
+    });
+}
+
+pub fn queue_move(from: usize, to: usize) {
+    let move_request = format!("queue-move {} {};", from, to);
+
+    Client::new(IP).safe_run(move_request.as_str(), |res| match res.status() {
+        200 => stdout(res.msg()),
+        _ => stderr(res.msg()),
+    });
+}
+
+pub fn queue_clear() {
+    Client::new(IP).safe_run("queue-clear;", |res| match res.status() {
+        200 => stdout(res.msg()),
+        _ => stderr(res.msg()),
+    });
+}
+
+pub fn queue_next(force: bool) {
+    let next_request = match force {
+        true => "queue-next\nforce: true;",
+        false => "queue-next;",
+    };
+
+    Client::new(IP).safe_run(next_request, |res| match res.status() {
+        201 => stdout(res.msg()),
+        _ => stderr(res.msg()),
+    });
+}
+
+pub fn schedule_list() {
+    Client::new(IP).safe_run("schedule-list;", |res| match res.status() {
+        200 => stdout(res.msg()),
+        _ => stderr(res.msg()),
+    });
+}
+
 pub fn pause_resume_counter() {
     Client::new(IP).safe_run("pause-resume-counter;", |res| match res.status() {
         200 => stdout(res.msg()),
@@ -77,6 +329,130 @@ pub fn pause_resume_counter() {
     });
 }
 
+pub fn pause_counter(resume_after: Option<&str>, halt_after: Option<&str>) {
+    let pause_request = match get_pause_request(resume_after, halt_after) {
+        Ok(req) => req,
+        Err(e) => return stderr(e.as_str()),
+    };
+
+    Client::new(IP).safe_run(pause_request.as_str(), |res| match res.status() {
+        200 | 304 => stdout(res.msg()),
+        _ => stderr(res.msg()),
+    });
+}
+
+pub fn resume_counter() {
+    Client::new(IP).safe_run("resume;", |res| match res.status() {
+        200 | 304 => stdout(res.msg()),
+        _ => stderr(res.msg()),
+    });
+}
+
+pub fn interrupt(source: Source, note: Option<&str>, pause: bool) {
+    let mut interrupt_request = format!("interrupt {}", source);
+
+    if let Some(note) = note {
+        interrupt_request.push_str(format!("\nnote: {}", escape(note)).as_str());
+    }
+
+    if pause {
+        interrupt_request.push_str("\npause: true");
+    }
+
+    interrupt_request.push(';');
+
+    Client::new(IP).safe_run(interrupt_request.as_str(), |res| match res.status() {
+        201 => stdout(res.msg()),
+        _ => stderr(res.msg()),
+    });
+}
+
+pub fn note(text: &str) {
+    let text = text.trim();
+
+    if text.is_empty() {
+        return stderr("Empty note, nothing added.");
+    }
+
+    let note_request = format!("note\ntext: {};", escape(text));
+
+    Client::new(IP).safe_run(note_request.as_str(), |res| match res.status() {
+        201 => stdout(res.msg()),
+        _ => stderr(res.msg()),
+    });
+}
+
+/// Asks for a one line summary on the terminal.
+pub fn prompt_note() {
+    print!("What got done? ");
+    let _ = io::stdout().flush();
+
+    let mut text = String::new();
+
+    match io::stdin().read_line(&mut text) {
+        Ok(_) => note(&text),
+        Err(e) => stderr(format!("Failed to read note: {}", e).as_str()),
+    }
+}
+
+/// Writes the note in `$VISUAL` or `$EDITOR`, lines starting with `#` are left out.
+pub fn edit_note() {
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .unwrap_or("vi".to_owned());
+    let path = env::temp_dir().join(format!("pdoro-note-{}.txt", process::id()));
+
+    if let Err(e) = fs::write(
+        &path,
+        "\n# What got done? Lines starting with # are ignored, an empty note adds nothing.\n",
+    ) {
+        return stderr(format!("Failed to create note file: {}", e).as_str());
+    }
+
+    let edited = Command::new(&editor)
+        .arg(&path)
+        .status()
+        .map_err(|e| format!("Failed to run {}: {}", editor, e))
+        .and_then(|status| match status.success() {
+            true => fs::read_to_string(&path).map_err(|e| e.to_string()),
+            false => Err(format!("{} exited with {}", editor, status)),
+        });
+
+    let _ = fs::remove_file(&path);
+
+    match edited {
+        Ok(text) => note(
+            &text
+                .lines()
+                .filter(|line| !line.starts_with('#'))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        ),
+        Err(e) => stderr(e.as_str()),
+    }
+}
+
+pub fn acknowledge() {
+    Client::new(IP).safe_run("ack;", |res| match res.status() {
+        200 => stdout(res.msg()),
+        _ => stderr(res.msg()),
+    });
+}
+
+pub fn extend(time: &str) {
+    let seconds = match Time::new(time, &Bounds::default()) {
+        Ok(time) => time.get_seconds(),
+        Err(e) => return stderr(e.to_string().as_str()),
+    };
+
+    Client::new(IP).safe_run(format!("extend {};", seconds).as_str(), |res| {
+        match res.status() {
+            200 => stdout(res.msg()),
+            _ => stderr(res.msg()),
+        }
+    });
+}
+
 pub fn halt_counter() {
     Client::new(IP).safe_run("halt-counter;", |res| match res.status() {
         200 => stdout(res.msg()),
@@ -86,19 +462,40 @@ pub fn halt_counter() {
 
 pub fn start_server() {
     match Client::new(IP).run("healthcheck;") {
-        Ok(_) => return stderr("Pomodoro server already running."),
+        Ok(_) => stderr("Pomodoro server already running."),
         Err(ClientError::ServerNotStarted) => {
+            match Config::load() {
+                Ok(config) => Config::set_global(config),
+                Err(e) => return stderr(e.as_str()),
+            }
+
             println!("starting...");
             start_daemon_server()
         }
-        Err(e) => return stderr(format!("Error: {:?}", e).as_str()),
+        Err(e) => stderr(format!("Error: {:?}", e).as_str()),
     }
 }
 
-fn get_start_request(time_arg: &str, callback_with_args: &str) -> Result<String, String> {
-    let seconds = Time::new(time_arg)?.get_seconds();
+fn get_pause_request(
+    resume_after: Option<&str>,
+    halt_after: Option<&str>,
+) -> Result<String, String> {
+    let to_seconds = |arg: Option<&str>| match arg {
+        Some(time_arg) => Time::new(time_arg, &Bounds::default())
+            .map(|t| t.get_seconds())
+            .map_err(|e| e.to_string()),
+        None => Ok(0),
+    };
+
+    Ok(format!(
+        "pause {} {};",
+        to_seconds(resume_after)?,
+        to_seconds(halt_after)?
+    ))
+}
 
-    Ok(format!("start {} {};", seconds, callback_with_args))
+fn get_profile(name: Option<&str>) -> Result<Profile, String> {
+    Config::load()?.profile(name)
 }
 
 fn start_daemon_server() {
@@ -109,7 +506,7 @@ fn start_daemon_server() {
         .working_directory("/tmp")
         .user("nobody")
         .group("pdoro_daemon")
-        .umask(0o777)
+        .umask(0o022) // history must stay readable by the client
         .stdout(stdout_file)
         .stderr(stderr_file)
         .privileged_action(|| "Executed before drop privileges");
@@ -119,5 +516,8 @@ fn start_daemon_server() {
         Err(e) => eprintln!("Error, {}", e),
     }
 
+    queue::load();
+    scheduler::spawn();
+    status_file::update();
     Server::new(IP).run(TCPHandler);
 }
@@ -0,0 +1,237 @@
+use chrono::{DateTime, Local, NaiveDate, Utc};
+
+use crate::args::ExportFormat;
+use crate::config::Config;
+use crate::history::{self, Kind, Outcome, Record};
+use crate::time::Time;
+use crate::utils::stderr;
+
+/// Prints recorded sessions started between `from` and `to` (inclusive days).
+pub fn export(format: ExportFormat, from: Option<&str>, to: Option<&str>) {
+    let records = match load(from, to) {
+        Ok(records) => records,
+        Err(e) => return stderr(e.as_str()),
+    };
+
+    let output = match format {
+        ExportFormat::Csv => Ok(to_csv(&records)),
+        ExportFormat::Json => serde_json::to_string_pretty(&records)
+            .map(|json| json + "\n")
+            .map_err(|e| e.to_string()),
+        ExportFormat::Ics => Ok(to_ics(&records)),
+    };
+
+    // printed as is, ics lines end in CRLF
+    match output {
+        Ok(output) => print!("{}", output),
+        Err(e) => stderr(e.as_str()),
+    }
+}
+
+fn load(from: Option<&str>, to: Option<&str>) -> Result<Vec<Record>, String> {
+    let parse = |date: Option<&str>| {
+        date.map(|date| {
+            NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                .map_err(|_| format!("Invalid date '{}': expected YYYY-MM-DD", date))
+        })
+        .transpose()
+    };
+
+    let (from, to) = (parse(from)?, parse(to)?);
+    let config = Config::load()?;
+
+    let mut records: Vec<Record> = history::read(&config.history_path())?
+        .into_iter()
+        .filter(|record| {
+            let day = config.day_of(&record.started_at);
+            from.is_none_or(|from| day >= from) && to.is_none_or(|to| day <= to)
+        })
+        .collect();
+
+    records.sort_by_key(|record| record.started_at);
+    Ok(records)
+}
+
+fn kind_name(kind: Kind) -> &'static str {
+    match kind {
+        Kind::Pomodoro => "pomodoro",
+        Kind::Stopwatch => "stopwatch",
+    }
+}
+
+fn outcome_name(outcome: Outcome) -> &'static str {
+    match outcome {
+        Outcome::Completed => "completed",
+        Outcome::Halted => "halted",
+    }
+}
+
+/// Quotes a field holding a separator, quote or line break.
+fn csv_field(field: &str) -> String {
+    match field.contains([',', '"', '\n', '\r']) {
+        true => format!("\"{}\"", field.replace('"', "\"\"")),
+        false => field.to_owned(),
+    }
+}
+
+/// One row per session, durations in seconds, interruptions and notes on
+/// separate lines.
+fn to_csv(records: &[Record]) -> String {
+    let mut csv = String::from(
+        "kind,phase,started_at,ended_at,duration,elapsed,paused,overtime,outcome,task,tags,interruptions,notes\n",
+    );
+
+    for record in records {
+        let interruptions: Vec<String> = record
+            .interruptions
+            .iter()
+            .map(|i| match &i.note {
+                // one line each, so they can be imported again
+                Some(note) => format!(
+                    "{} {} {}",
+                    i.at.to_rfc3339(),
+                    i.source,
+                    note.replace('\n', " ")
+                ),
+                None => format!("{} {}", i.at.to_rfc3339(), i.source),
+            })
+            .collect();
+
+        let fields = [
+            kind_name(record.kind).to_owned(),
+            record.phase.name().to_owned(),
+            record.started_at.to_rfc3339(),
+            record.ended_at.to_rfc3339(),
+            record.duration.map(|d| d.to_string()).unwrap_or_default(),
+            record.elapsed.to_string(),
+            record.paused.to_string(),
+            record.overtime.to_string(),
+            outcome_name(record.outcome).to_owned(),
+            record.labels.task.clone().unwrap_or_default(),
+            record.labels.tags.join(";"),
+            interruptions.join("\n"),
+            record.notes.join("\n"),
+        ];
+
+        let row: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+
+        csv.push_str(row.join(",").as_str());
+        csv.push('\n');
+    }
+
+    csv
+}
+
+/// Escapes TEXT values as RFC 5545 requires, line breaks of any kind as
+/// `\n` since a bare CR would end the content line.
+fn ics_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\n")
+        .replace('\r', "\n")
+        .replace('\n', "\\n")
+}
+
+fn ics_time(time: &DateTime<Local>) -> String {
+    time.with_timezone(&Utc)
+        .format("%Y%m%dT%H%M%SZ")
+        .to_string()
+}
+
+/// Folds lines longer than 75 octets, continuation lines start with a space.
+fn ics_line(ics: &mut String, line: &str) {
+    let mut len = 0;
+
+    for c in line.chars() {
+        if len + c.len_utf8() > 75 {
+            ics.push_str("\r\n ");
+            len = 1;
+        }
+
+        ics.push(c);
+        len += c.len_utf8();
+    }
+
+    ics.push_str("\r\n");
+}
+
+/// One VEVENT per session, spanning from its start to its end.
+fn to_ics(records: &[Record]) -> String {
+    let mut ics = String::new();
+    let now = ics_time(&Local::now());
+
+    ics_line(&mut ics, "BEGIN:VCALENDAR");
+    ics_line(&mut ics, "VERSION:2.0");
+    ics_line(&mut ics, "PRODID:-//pdoro//pdoro//EN");
+
+    for record in records {
+        let summary = match &record.labels.task {
+            Some(task) => task.clone(),
+            None => match record.kind {
+                Kind::Pomodoro => "Pomodoro".to_owned(),
+                Kind::Stopwatch => "Stopwatch".to_owned(),
+            },
+        };
+
+        let mut description = vec![format!(
+            "{}, {} focused",
+            outcome_name(record.outcome),
+            Time::get_duration_from_seconds(&record.elapsed)
+        )];
+
+        if record.paused > 0 {
+            description.push(format!(
+                "paused {}",
+                Time::get_duration_from_seconds(&record.paused)
+            ));
+        }
+
+        match record.interruptions.len() {
+            0 => {}
+            1 => description.push("1 interruption".to_owned()),
+            count => description.push(format!("{} interruptions", count)),
+        }
+
+        let mut description = description.join(", ");
+
+        for note in &record.notes {
+            description.push_str(format!("\n- {}", note).as_str());
+        }
+
+        ics_line(&mut ics, "BEGIN:VEVENT");
+        ics_line(
+            &mut ics,
+            format!(
+                "UID:{}-{}@pdoro",
+                record.started_at.timestamp(),
+                kind_name(record.kind)
+            )
+            .as_str(),
+        );
+        ics_line(&mut ics, format!("DTSTAMP:{}", now).as_str());
+        ics_line(
+            &mut ics,
+            format!("DTSTART:{}", ics_time(&record.started_at)).as_str(),
+        );
+        ics_line(
+            &mut ics,
+            format!("DTEND:{}", ics_time(&record.ended_at)).as_str(),
+        );
+        ics_line(&mut ics, format!("SUMMARY:{}", ics_text(&summary)).as_str());
+
+        if !record.labels.tags.is_empty() {
+            let tags: Vec<String> = record.labels.tags.iter().map(|t| ics_text(t)).collect();
+            ics_line(&mut ics, format!("CATEGORIES:{}", tags.join(",")).as_str());
+        }
+
+        ics_line(
+            &mut ics,
+            format!("DESCRIPTION:{}", ics_text(&description)).as_str(),
+        );
+        ics_line(&mut ics, "END:VEVENT");
+    }
+
+    ics_line(&mut ics, "END:VCALENDAR");
+    ics
+}
@@ -0,0 +1,531 @@
+use std::collections::HashSet;
+use std::fs;
+use std::io::{self, Read};
+use std::path::Path;
+
+use chrono::{DateTime, Duration, Local, NaiveDateTime, TimeZone};
+
+use crate::args::ImportFormat;
+use crate::config::Config;
+use crate::header::escape;
+use crate::history::{self, Interruption, Kind, Outcome, Record, Source};
+use crate::labels::Labels;
+use crate::phase::Phase;
+use crate::time::Time;
+use crate::utils::{stderr, stdout};
+
+use crate::server::MAX_REQUEST;
+
+use super::actions::IP;
+use super::{Client, ClientError};
+
+/// Escaped records sent to the server per request, as many as its request
+/// limit leaves room for next to `import\nrecords: ;`.
+const BATCH_BYTES: usize = MAX_REQUEST - "import\nrecords: ;".len();
+
+/// Separates escaped records, an escaped newline.
+const SEPARATOR: &str = "%0A";
+
+/// Reads the sessions of one export format.
+trait Mapper {
+    fn records(&self, content: &str) -> Result<Vec<Record>, String>;
+}
+
+/// `pdoro export --format json`, or a history file of json lines.
+struct Json;
+
+impl Mapper for Json {
+    fn records(&self, content: &str) -> Result<Vec<Record>, String> {
+        if let Ok(records) = serde_json::from_str(content) {
+            return Ok(records);
+        }
+
+        content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| {
+                serde_json::from_str(line)
+                    .map_err(|e| format!("Invalid record on line {}: {}", i + 1, e))
+            })
+            .collect()
+    }
+}
+
+/// `pdoro export --format csv`.
+struct Csv;
+
+impl Csv {
+    /// Lines of `<time> <source> [note]`, as exported.
+    fn interruptions(field: &str) -> Result<Vec<Interruption>, String> {
+        field
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                let mut parts = line.splitn(3, ' ');
+
+                Ok(Interruption {
+                    at: parse_time(parts.next().unwrap_or_default())?,
+                    source: match parts.next() {
+                        Some("external") => Source::External,
+                        Some("internal") => Source::Internal,
+                        _ => return Err(format!("Invalid interruption '{}'", line)),
+                    },
+                    note: parts.next().map(|note| note.to_owned()),
+                })
+            })
+            .collect()
+    }
+}
+
+impl Mapper for Csv {
+    fn records(&self, content: &str) -> Result<Vec<Record>, String> {
+        let table = Table::parse(content)?;
+
+        table
+            .rows
+            .iter()
+            .enumerate()
+            .map(|(i, row)| {
+                let field = |name| table.field(row, name).unwrap_or_default();
+                let number = |name| field(name).parse::<u32>().unwrap_or_default();
+                let to_error = |e: String| format!("Row {}: {}", i + 2, e);
+
+                Ok(Record {
+                    kind: match field("kind") {
+                        "stopwatch" => Kind::Stopwatch,
+                        _ => Kind::Pomodoro,
+                    },
+                    phase: match field("phase") {
+                        "break" => Phase::Break,
+                        _ => Phase::Work,
+                    },
+                    started_at: parse_time(field("started_at")).map_err(to_error)?,
+                    ended_at: parse_time(field("ended_at")).map_err(to_error)?,
+                    duration: field("duration").parse().ok(),
+                    elapsed: number("elapsed"),
+                    paused: number("paused"),
+                    overtime: number("overtime"),
+                    labels: Labels::new(
+                        Some(field("task")),
+                        Some(&field("tags").replace(';', ",")),
+                    ),
+                    interruptions: Self::interruptions(field("interruptions")).map_err(to_error)?,
+                    notes: field("notes")
+                        .lines()
+                        .filter(|note| !note.is_empty())
+                        .map(|note| note.to_owned())
+                        .collect(),
+                    outcome: match field("outcome") {
+                        "halted" => Outcome::Halted,
+                        _ => Outcome::Completed,
+                    },
+                })
+            })
+            .collect()
+    }
+}
+
+/// Csv from any tool, mapping its columns to `start`, `end`, `duration`,
+/// `task` and `tags`. A value of `A+B` joins columns `A` and `B` with a
+/// space, for tools that split dates and times.
+struct Columns {
+    columns: Vec<(String, String)>,
+    kind: Kind,
+}
+
+impl Columns {
+    fn new(mapping: &[String], kind: Kind) -> Result<Self, String> {
+        let columns = mapping
+            .iter()
+            .map(|column| match column.split_once('=') {
+                Some((key, name)) => Ok((key.trim().to_owned(), name.trim().to_owned())),
+                None => Err(format!("Invalid column '{}': expected key=Column", column)),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        match columns.iter().any(|(key, _)| key == "start") {
+            true => Ok(Self { columns, kind }),
+            false => Err("Missing start column, e.g. --column start=Start".to_owned()),
+        }
+    }
+
+    fn preset(columns: &[(&str, &str)]) -> Self {
+        Self {
+            columns: columns
+                .iter()
+                .map(|(key, name)| (key.to_string(), name.to_string()))
+                .collect(),
+            kind: Kind::Stopwatch,
+        }
+    }
+
+    fn toggl() -> Self {
+        Self::preset(&[
+            ("start", "Start date+Start time"),
+            ("end", "End date+End time"),
+            ("task", "Description"),
+            ("tags", "Tags"),
+        ])
+    }
+
+    fn clockify() -> Self {
+        Self::preset(&[
+            ("start", "Start Date+Start Time"),
+            ("end", "End Date+End Time"),
+            ("task", "Description"),
+            ("tags", "Tags"),
+        ])
+    }
+
+    fn value(&self, table: &Table, row: &[String], key: &str) -> Option<String> {
+        let (_, names) = self.columns.iter().find(|(k, _)| k == key)?;
+
+        let values = names
+            .split('+')
+            .map(|name| table.field(row, name.trim()))
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(values.join(" ").trim().to_owned()).filter(|value| !value.is_empty())
+    }
+}
+
+impl Mapper for Columns {
+    fn records(&self, content: &str) -> Result<Vec<Record>, String> {
+        let table = Table::parse(content)?;
+
+        table
+            .rows
+            .iter()
+            .enumerate()
+            .map(|(i, row)| {
+                let value = |key| self.value(&table, row, key);
+                let to_error = |e: String| format!("Row {}: {}", i + 2, e);
+
+                let started_at =
+                    parse_time(&value("start").unwrap_or_default()).map_err(to_error)?;
+
+                let duration = value("duration")
+                    .map(|d| {
+                        Time::parse(&d)
+                            .map(|t| t.get_seconds())
+                            .map_err(|e| e.to_string())
+                    })
+                    .transpose()
+                    .map_err(to_error)?;
+
+                let ended_at = match (value("end"), duration) {
+                    (Some(end), _) => parse_time(&end).map_err(to_error)?,
+                    (None, Some(duration)) => started_at + Duration::seconds(duration as i64),
+                    (None, None) => return Err(to_error("missing end or duration".to_owned())),
+                };
+
+                let span = (ended_at - started_at).num_seconds().max(0) as u32;
+                let elapsed = duration.unwrap_or(span).min(span);
+
+                Ok(Record {
+                    kind: self.kind,
+                    phase: Phase::Work,
+                    started_at,
+                    ended_at,
+                    duration: match self.kind {
+                        Kind::Pomodoro => Some(elapsed),
+                        Kind::Stopwatch => None,
+                    },
+                    elapsed,
+                    paused: span - elapsed,
+                    overtime: 0,
+                    labels: Labels::new(
+                        value("task").as_deref(),
+                        value("tags").map(|tags| tags.replace(';', ",")).as_deref(),
+                    ),
+                    interruptions: vec![],
+                    notes: vec![],
+                    outcome: Outcome::Completed,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Csv with a header row, quoted fields may hold separators, quotes and
+/// line breaks.
+struct Table {
+    header: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    fn parse(content: &str) -> Result<Self, String> {
+        let mut rows = vec![];
+        let mut row = vec![];
+        let mut field = String::new();
+        let mut quoted = false;
+        let mut chars = content.trim_start_matches('\u{feff}').chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match (c, quoted) {
+                ('"', true) if chars.peek() == Some(&'"') => {
+                    field.push('"');
+                    chars.next();
+                }
+                ('"', _) => quoted = !quoted,
+                (',', false) => row.push(std::mem::take(&mut field)),
+                ('\r', false) => {}
+                ('\n', false) => {
+                    row.push(std::mem::take(&mut field));
+                    rows.push(std::mem::take(&mut row));
+                }
+                (c, _) => field.push(c),
+            }
+        }
+
+        if !field.is_empty() || !row.is_empty() {
+            row.push(field);
+            rows.push(row);
+        }
+
+        rows.retain(|row| row.iter().any(|field| !field.is_empty()));
+
+        match rows.is_empty() {
+            true => Err("No header row.".to_owned()),
+            false => Ok(Self {
+                header: rows.remove(0),
+                rows,
+            }),
+        }
+    }
+
+    fn field<'a>(&self, row: &'a [String], name: &str) -> Option<&'a str> {
+        let index = self.header.iter().position(|column| column == name)?;
+        row.get(index).map(|field| field.as_str())
+    }
+}
+
+/// RFC 3339, or a local date and time in one of the usual notations.
+fn parse_time(input: &str) -> Result<DateTime<Local>, String> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(input) {
+        return Ok(time.with_timezone(&Local));
+    }
+
+    [
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%d %H:%M",
+        "%Y-%m-%dT%H:%M:%S",
+        "%m/%d/%Y %I:%M:%S %p",
+        "%m/%d/%Y %I:%M %p",
+        "%m/%d/%Y %H:%M:%S",
+        "%m/%d/%Y %H:%M",
+    ]
+    .iter()
+    .find_map(|format| NaiveDateTime::parse_from_str(input, format).ok())
+    .and_then(|time| Local.from_local_datetime(&time).earliest())
+    .ok_or(format!("Invalid time '{}'", input))
+}
+
+/// Adds the sessions in `path` (`-` for stdin) to history, skipping those
+/// starting at the same second as one already recorded.
+pub fn import(path: &str, format: ImportFormat, columns: &[String], dry_run: bool) {
+    let mapper: Box<dyn Mapper> = match format {
+        ImportFormat::Json => Box::new(Json),
+        ImportFormat::Csv => Box::new(Csv),
+        ImportFormat::Toggl => Box::new(Columns::toggl()),
+        ImportFormat::Clockify => Box::new(Columns::clockify()),
+        ImportFormat::Columns => match Columns::new(columns, Kind::Pomodoro) {
+            Ok(columns) => Box::new(columns),
+            Err(e) => return stderr(e.as_str()),
+        },
+    };
+
+    let content = match path {
+        "-" => {
+            let mut content = String::new();
+            io::stdin().read_to_string(&mut content).map(|_| content)
+        }
+        _ => fs::read_to_string(path),
+    };
+
+    let content = match content {
+        Ok(content) => content,
+        Err(e) => return stderr(format!("Failed to read {}: {}", path, e).as_str()),
+    };
+
+    let history_path = match Config::load() {
+        Ok(config) => config.history_path(),
+        Err(e) => return stderr(e.as_str()),
+    };
+
+    let mut records = match mapper.records(&content) {
+        Ok(records) => records,
+        Err(e) => return stderr(e.as_str()),
+    };
+
+    records.sort_by_key(|record| record.started_at);
+
+    let imported = match dry_run {
+        true => count_missing(&history_path, &records),
+        false => append(&history_path, &records),
+    };
+
+    let imported = match imported {
+        Ok(imported) => imported,
+        Err(e) => return stderr(e.as_str()),
+    };
+
+    stdout(
+        format!(
+            "{} {} {}, skipped {} already recorded.",
+            match dry_run {
+                true => "Would import",
+                false => "Imported",
+            },
+            imported,
+            match imported {
+                1 => "session",
+                _ => "sessions",
+            },
+            records.len() - imported
+        )
+        .as_str(),
+    )
+}
+
+/// How many of `records` `history::append_missing` would append.
+fn count_missing(history_path: &Path, records: &[Record]) -> Result<usize, String> {
+    let mut starts: HashSet<i64> = history::read(history_path)?
+        .iter()
+        .map(|record| record.started_at.timestamp())
+        .collect();
+
+    Ok(records
+        .iter()
+        .filter(|record| starts.insert(record.started_at.timestamp()))
+        .count())
+}
+
+/// Appends the records through the server, which also writes history and
+/// counts today's sessions, or directly when it is not running.
+fn append(history_path: &Path, records: &[Record]) -> Result<usize, String> {
+    let lines = records
+        .iter()
+        .map(serde_json::to_string)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut imported = 0;
+
+    for batch in batches(&lines, BATCH_BYTES)? {
+        let import_request = format!("import\nrecords: {};", batch);
+
+        match Client::new(IP).run(import_request.as_str()) {
+            Ok(res) if res.status() == 201 => {
+                imported += res.msg().parse::<usize>().unwrap_or_default()
+            }
+            Ok(res) => return Err(res.msg().to_owned()),
+            Err(ClientError::ServerNotStarted) if imported == 0 => {
+                return history::append_missing(history_path, records)
+            }
+            Err(e) => return Err(format!("Error: {:?}", e)),
+        }
+    }
+
+    Ok(imported)
+}
+
+/// Escapes the lines and joins them into batches of at most `limit` bytes,
+/// starting a new one before a line that would not fit.
+fn batches(lines: &[String], limit: usize) -> Result<Vec<String>, String> {
+    let mut batches = vec![];
+    let mut batch = String::new();
+
+    for (i, line) in lines.iter().enumerate() {
+        let line = escape(line);
+
+        if line.len() > limit {
+            return Err(format!(
+                "Record {} is too large to import: {} bytes escaped, the limit is {}.",
+                i + 1,
+                line.len(),
+                limit
+            ));
+        }
+
+        if !batch.is_empty() && batch.len() + SEPARATOR.len() + line.len() > limit {
+            batches.push(std::mem::take(&mut batch));
+        }
+
+        if !batch.is_empty() {
+            batch.push_str(SEPARATOR);
+        }
+
+        batch.push_str(&line);
+    }
+
+    if !batch.is_empty() {
+        batches.push(batch);
+    }
+
+    Ok(batches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|line| line.to_string()).collect()
+    }
+
+    #[test]
+    fn batches_by_escaped_length() {
+        let cases = [
+            (vec![], 10, vec![]),
+            (vec!["aaaa"], 10, vec!["aaaa"]),
+            (vec!["aa", "bb"], 7, vec!["aa%0Abb"]),
+            (vec!["aa", "bb", "cc"], 7, vec!["aa%0Abb", "cc"]),
+            (vec!["aaaa", "bbbb"], 10, vec!["aaaa", "bbbb"]),
+            // 4 bytes raw, 10 escaped
+            (vec!["a;;;", "b"], 10, vec!["a%3B%3B%3B", "b"]),
+            (vec!["a", "b;;"], 10, vec!["a", "b%3B%3B"]),
+            (vec!["%\n"], 6, vec!["%25%0A"]),
+        ];
+
+        for (input, limit, expected) in cases {
+            assert_eq!(
+                batches(&lines(&input), limit).unwrap(),
+                lines(&expected),
+                "{:?}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn refuses_a_record_larger_than_a_batch() {
+        let input = lines(&["a", "b;;;;"]);
+
+        assert_eq!(
+            batches(&input, 10),
+            Err("Record 2 is too large to import: 13 bytes escaped, the limit is 10.".to_owned())
+        );
+    }
+
+    #[test]
+    fn fills_batches_up_to_the_request_limit() {
+        let record = "x".repeat(1000);
+        let input = vec![record; 200];
+
+        let batches = batches(&input, BATCH_BYTES).unwrap();
+
+        for (i, batch) in batches.iter().enumerate() {
+            let request = format!("import\nrecords: {};", batch);
+
+            assert!(request.len() <= MAX_REQUEST);
+
+            // only the last one is left short of another record
+            if i + 1 < batches.len() {
+                assert!(request.len() + 1003 > MAX_REQUEST);
+            }
+        }
+    }
+}
@@ -0,0 +1,384 @@
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, Duration, Local, NaiveDate};
+
+use crate::args::{GroupBy, TimesheetFormat};
+use crate::config::{Config, Rounding};
+use crate::history::{self, Interruption, Kind, Outcome, Record, Source};
+use crate::phase::Phase;
+use crate::time::Time;
+use crate::utils::{stderr, stdout};
+
+/// Config, recorded sessions and the day to report on, `YYYY-MM-DD` or
+/// today when not given.
+pub fn load(date: Option<&str>) -> Result<(Config, Vec<Record>, NaiveDate), String> {
+    let config = Config::load()?;
+    let records = history::read(&config.history_path())?;
+
+    let date = match date {
+        Some(date) => parse_date(date)?,
+        None => config.day_of(&Local::now()),
+    };
+
+    Ok((config, records, date))
+}
+
+fn parse_date(date: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map_err(|_| format!("Invalid date '{}': expected YYYY-MM-DD", date))
+}
+
+/// Prints the sessions started on `date` with what got done in them.
+pub fn sessions(date: Option<&str>) {
+    let (config, records, date) = match load(date) {
+        Ok(loaded) => loaded,
+        Err(e) => return stderr(e.as_str()),
+    };
+
+    let mut records: Vec<&Record> = records
+        .iter()
+        .filter(|record| config.day_of(&record.started_at) == date)
+        .collect();
+
+    if records.is_empty() {
+        return stdout(format!("No sessions on {}.", date).as_str());
+    }
+
+    records.sort_by_key(|record| record.started_at);
+
+    let mut lines = vec![];
+
+    for record in records {
+        lines.push(session_line(record));
+
+        for note in &record.notes {
+            lines.push(format!("  - {}", note.replace('\n', "\n    ")));
+        }
+    }
+
+    stdout(lines.join("\n").as_str())
+}
+
+/// Times, kind, outcome, focused time and labels of a session.
+pub fn session_line(record: &Record) -> String {
+    let mut line = format!(
+        "{}-{}  {:<9}  {:<9}  {}",
+        record.started_at.format("%H:%M"),
+        record.ended_at.format("%H:%M"),
+        match (record.kind, record.phase) {
+            (Kind::Pomodoro, Phase::Work) => "pomodoro",
+            (Kind::Pomodoro, Phase::Break) => "break",
+            (Kind::Stopwatch, _) => "stopwatch",
+        },
+        match record.outcome {
+            Outcome::Completed => "completed",
+            Outcome::Halted => "halted",
+        },
+        Time::get_duration_from_seconds(&record.elapsed)
+    );
+
+    if !record.labels.is_empty() {
+        line.push_str(format!("  {}", record.labels).as_str());
+    }
+
+    line
+}
+
+/// Time, source and note of an interruption, with the labels of its session.
+pub fn interruption_line(interruption: &Interruption, record: &Record) -> String {
+    let mut line = format!(
+        "{}  {:<8}  {}",
+        interruption.at.format("%H:%M"),
+        interruption.source,
+        interruption.note.as_deref().unwrap_or("-")
+    );
+
+    if !record.labels.is_empty() {
+        line.push_str(format!("  ({})", record.labels).as_str());
+    }
+
+    line
+}
+
+/// Prints the interruptions of finished sessions on `date`, oldest first.
+pub fn interruptions(date: Option<&str>) {
+    let (config, records, date) = match load(date) {
+        Ok(loaded) => loaded,
+        Err(e) => return stderr(e.as_str()),
+    };
+
+    let mut interruptions: Vec<(&Interruption, &Record)> = records
+        .iter()
+        .flat_map(|record| record.interruptions.iter().map(move |i| (i, record)))
+        .filter(|(interruption, _)| config.day_of(&interruption.at) == date)
+        .collect();
+
+    if interruptions.is_empty() {
+        return stdout(format!("No interruptions on {}.", date).as_str());
+    }
+
+    interruptions.sort_by_key(|(interruption, _)| interruption.at);
+
+    let internal = interruptions
+        .iter()
+        .filter(|(interruption, _)| interruption.source == Source::Internal)
+        .count();
+
+    let mut lines = vec![format!(
+        "{}: {} interruptions, {} external, {} internal",
+        date,
+        interruptions.len(),
+        interruptions.len() - internal,
+        internal
+    )];
+
+    for (interruption, record) in interruptions {
+        lines.push(interruption_line(interruption, record));
+    }
+
+    stdout(lines.join("\n").as_str())
+}
+
+/// Prints focused time, overtime included, per task or tag and day between
+/// `from` and `to`, the current week when not given. Every cell is rounded
+/// on its own so the totals add up.
+pub fn timesheet(
+    range: Option<(&str, &str)>,
+    by: GroupBy,
+    round: Option<&str>,
+    format: TimesheetFormat,
+) {
+    let (config, records, today) = match load(None) {
+        Ok(loaded) => loaded,
+        Err(e) => return stderr(e.as_str()),
+    };
+
+    let range = range.map(|(from, to)| parse_date(from).and_then(|f| Ok((f, parse_date(to)?))));
+
+    let (from, to) = match range {
+        Some(Ok((from, to))) if from <= to => (from, to),
+        Some(Ok(_)) => return stderr("Invalid range: --from is after --to"),
+        Some(Err(e)) => return stderr(e.as_str()),
+        None => {
+            let monday = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+            (monday, monday + Duration::days(6))
+        }
+    };
+
+    let round = match round.map(Time::parse) {
+        Some(Ok(time)) => time.get_seconds(),
+        Some(Err(e)) => return stderr(e.to_string().as_str()),
+        None => config.timesheet_round(),
+    };
+
+    let days: Vec<NaiveDate> = from.iter_days().take_while(|day| *day <= to).collect();
+    let mut rows: BTreeMap<String, Vec<u32>> = BTreeMap::new();
+
+    for record in &records {
+        // breaks are not billed
+        if record.phase == Phase::Break {
+            continue;
+        }
+
+        let day = config.day_of(&record.started_at);
+
+        let index = match days.iter().position(|d| *d == day) {
+            Some(index) => index,
+            None => continue,
+        };
+
+        let keys = match by {
+            GroupBy::Task => vec![record.labels.task.clone().unwrap_or("(no task)".to_owned())],
+            GroupBy::Tag if record.labels.tags.is_empty() => vec!["(untagged)".to_owned()],
+            GroupBy::Tag => record.labels.tags.clone(),
+        };
+
+        for key in keys {
+            rows.entry(key).or_insert(vec![0; days.len()])[index] +=
+                record.elapsed + record.overtime;
+        }
+    }
+
+    if rows.is_empty() {
+        return stdout(format!("No sessions between {} and {}.", from, to).as_str());
+    }
+
+    let rounding = config.timesheet_rounding();
+
+    for cells in rows.values_mut() {
+        for cell in cells.iter_mut() {
+            *cell = round_to(*cell, round, rounding);
+        }
+    }
+
+    // sessions rounded away leave nothing to bill
+    rows.retain(|_, cells| cells.iter().any(|cell| *cell > 0));
+
+    if rows.is_empty() {
+        return stdout(format!("Nothing left between {} and {} once rounded.", from, to).as_str());
+    }
+
+    let name = match by {
+        GroupBy::Task => "Task",
+        GroupBy::Tag => "Tag",
+    };
+
+    let output = match format {
+        TimesheetFormat::Text => timesheet_table(name, &days, &rows, false),
+        TimesheetFormat::Markdown => timesheet_table(name, &days, &rows, true),
+        TimesheetFormat::Csv => timesheet_csv(name, &days, &rows),
+    };
+
+    stdout(output.as_str())
+}
+
+fn round_to(seconds: u32, increment: u32, rounding: Rounding) -> u32 {
+    if increment == 0 {
+        return seconds;
+    }
+
+    let rounded = match rounding {
+        Rounding::Nearest => (seconds + increment / 2) / increment,
+        Rounding::Up => seconds.div_ceil(increment),
+        Rounding::Down => seconds / increment,
+    };
+
+    rounded * increment
+}
+
+/// `h:mm`, to the nearest minute.
+fn hours_minutes(seconds: u32) -> String {
+    let minutes = (seconds + 30) / 60;
+    format!("{}:{:02}", minutes / 60, minutes % 60)
+}
+
+/// Rows of the matrix with a total column, followed by a total row.
+fn timesheet_rows<F: Fn(u32) -> String>(
+    name: &str,
+    days: &[NaiveDate],
+    rows: &BTreeMap<String, Vec<u32>>,
+    format_day: fn(&NaiveDate) -> String,
+    format_cell: F,
+) -> Vec<Vec<String>> {
+    let mut table = vec![];
+    let mut totals = vec![0; days.len()];
+
+    let mut header = vec![name.to_owned()];
+    header.extend(days.iter().map(format_day));
+    header.push("Total".to_owned());
+    table.push(header);
+
+    for (key, cells) in rows {
+        let mut row = vec![key.clone()];
+
+        for (i, cell) in cells.iter().enumerate() {
+            totals[i] += cell;
+            row.push(format_cell(*cell));
+        }
+
+        row.push(format_cell(cells.iter().sum()));
+        table.push(row);
+    }
+
+    let mut total_row = vec!["Total".to_owned()];
+    total_row.extend(totals.iter().map(|total| format_cell(*total)));
+    total_row.push(format_cell(totals.iter().sum()));
+    table.push(total_row);
+
+    table
+}
+
+/// Aligned plain text, or a Markdown table when `markdown`.
+fn timesheet_table(
+    name: &str,
+    days: &[NaiveDate],
+    rows: &BTreeMap<String, Vec<u32>>,
+    markdown: bool,
+) -> String {
+    let mut table = timesheet_rows(
+        name,
+        days,
+        rows,
+        |day| day.format("%a %m-%d").to_string(),
+        |seconds| match seconds {
+            0 => String::new(),
+            seconds => hours_minutes(seconds),
+        },
+    );
+
+    // a pipe in a task or tag would end the cell
+    if markdown {
+        for cell in table.iter_mut().flatten() {
+            *cell = cell.replace('|', "\\|");
+        }
+    }
+
+    let widths: Vec<usize> = (0..table[0].len())
+        .map(|column| {
+            table
+                .iter()
+                .map(|row| row[column].chars().count())
+                .max()
+                .unwrap_or_default()
+        })
+        .collect();
+
+    let mut lines = vec![];
+
+    for (i, row) in table.iter().enumerate() {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .enumerate()
+            .map(|(column, (cell, width))| match column {
+                0 => format!("{:<width$}", cell, width = width),
+                _ => format!("{:>width$}", cell, width = width),
+            })
+            .collect();
+
+        match markdown {
+            true => lines.push(format!("| {} |", cells.join(" | "))),
+            false => lines.push(cells.join("  ")),
+        }
+
+        if markdown && i == 0 {
+            let rules: Vec<String> = widths
+                .iter()
+                .enumerate()
+                .map(|(column, width)| match column {
+                    0 => "-".repeat(*width),
+                    _ => format!("{}:", "-".repeat(width - 1)),
+                })
+                .collect();
+
+            lines.push(format!("| {} |", rules.join(" | ")));
+        }
+    }
+
+    lines.join("\n")
+}
+
+/// Hours with two decimals, ready for a spreadsheet.
+fn timesheet_csv(name: &str, days: &[NaiveDate], rows: &BTreeMap<String, Vec<u32>>) -> String {
+    let table = timesheet_rows(
+        name,
+        days,
+        rows,
+        |day| day.format("%Y-%m-%d").to_string(),
+        |seconds| format!("{:.2}", seconds as f64 / 3600.0),
+    );
+
+    table
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|cell| match cell.contains([',', '"', '\n', '\r']) {
+                    true => format!("\"{}\"", cell.replace('"', "\"\"")),
+                    false => cell.clone(),
+                })
+                .collect::<Vec<_>>()
+                .join(",")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
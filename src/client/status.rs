@@ -0,0 +1,409 @@
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+
+use serde_json::json;
+
+use crate::args::StatusFormat;
+use crate::config::Config;
+use crate::header::unescape;
+use crate::history::Outcome;
+use crate::labels::Labels;
+use crate::phase::Phase;
+use crate::time::Time;
+use crate::utils::{stderr, stdout};
+
+use super::actions::IP;
+use super::response::Response;
+use super::template::Template;
+use super::{Client, ClientError};
+
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum Mode {
+    #[default]
+    Idle,
+    Countdown,
+    Stopwatch,
+    Overtime,
+    AwaitingAck,
+}
+
+impl Mode {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Idle => "idle",
+            Self::Countdown => "countdown",
+            Self::Stopwatch => "stopwatch",
+            Self::Overtime => "overtime",
+            Self::AwaitingAck => "awaiting-ack",
+        }
+    }
+}
+
+/// The timer as reported by the `remaining` route.
+#[derive(Default)]
+pub struct Status {
+    /// id of the current session
+    pub session: Option<u32>,
+    pub mode: Mode,
+    /// time left of a pomodoro, or counted up by a stopwatch or overtime
+    pub seconds: u32,
+    pub paused: bool,
+    pub phase: Phase,
+    /// seconds counted so far, pauses left out
+    pub elapsed: u32,
+    /// length of a pomodoro
+    pub total: Option<u32>,
+    pub labels: Labels,
+    pub interruptions: u32,
+    /// pomodoros completed today
+    pub pomodoros: u32,
+    pub goal: Option<String>,
+    /// the queued pomodoro that starts next
+    pub next: Option<String>,
+    /// id and outcome of the session that ended last
+    pub ended: Option<(u32, Outcome)>,
+}
+
+impl TryFrom<&Response> for Status {
+    type Error = String;
+
+    fn try_from(res: &Response) -> Result<Self, Self::Error> {
+        let msg = res.valid_msg()?;
+        let mut lines = msg.split('\n');
+        let mut parts = lines.next().unwrap_or_default().split(' ');
+
+        let seconds = parts
+            .next()
+            .unwrap_or_default()
+            .parse::<u32>()
+            .map_err(|_| "Failed to parse remaining time.")?;
+
+        let mode = match parts.next() {
+            Some("idle") => Mode::Idle,
+            Some("stopwatch") => Mode::Stopwatch,
+            Some("overtime") => Mode::Overtime,
+            Some("awaiting-ack") => Mode::AwaitingAck,
+            _ => Mode::Countdown,
+        };
+
+        let headers: Vec<(&str, String)> = lines
+            .filter_map(|l| l.split_once(": "))
+            .map(|(k, v)| (k, unescape(v)))
+            .collect();
+        let header = |name| {
+            headers
+                .iter()
+                .find(|(k, _)| *k == name)
+                .map(|(_, v)| v.as_str())
+        };
+        let number = |name| header(name).and_then(|v| v.parse::<u32>().ok());
+
+        let ended = header("ended").and_then(|ended| match ended.split_once(' ') {
+            Some((id, "completed")) => Some((id.parse().ok()?, Outcome::Completed)),
+            Some((id, "halted")) => Some((id.parse().ok()?, Outcome::Halted)),
+            _ => None,
+        });
+
+        Ok(Self {
+            session: number("session"),
+            mode,
+            seconds,
+            paused: res.status() == 304,
+            phase: match header("phase") {
+                Some("break") => Phase::Break,
+                _ => Phase::Work,
+            },
+            elapsed: number("elapsed").unwrap_or_default(),
+            total: number("total"),
+            labels: Labels::new(header("task"), header("tags")),
+            interruptions: number("interruptions").unwrap_or_default(),
+            pomodoros: number("pomodoros").unwrap_or_default(),
+            goal: header("goal").map(|goal| goal.to_owned()),
+            next: header("next").map(|next| next.to_owned()),
+            ended,
+        })
+    }
+}
+
+impl Status {
+    pub fn fetch() -> Result<Self, String> {
+        match Client::new(IP).run("remaining;") {
+            Ok(res) => Self::try_from(&res),
+            Err(ClientError::ServerNotStarted) => {
+                Err("Pdoro server has not been started.".to_owned())
+            }
+            Err(e) => Err(format!("Error: {:?}", e)),
+        }
+    }
+
+    /// A pomodoro at zero is about to be recorded, so it counts as idle.
+    pub fn is_idle(&self) -> bool {
+        match self.mode {
+            Mode::Idle => true,
+            Mode::Countdown => self.seconds == 0,
+            _ => false,
+        }
+    }
+
+    /// `idle`, `paused`, `break` or `running`, for status bar classes.
+    pub fn state(&self) -> &'static str {
+        match (self.is_idle(), self.paused, self.phase) {
+            (true, _, _) => "idle",
+            (_, true, _) => "paused",
+            (_, _, Phase::Break) => "break",
+            _ => "running",
+        }
+    }
+
+    /// How much of a pomodoro went by, full once it is over.
+    pub fn percentage(&self) -> u32 {
+        match (self.mode, self.total) {
+            (Mode::Countdown, Some(total)) if total > 0 => {
+                total.saturating_sub(self.seconds) * 100 / total
+            }
+            (Mode::Overtime | Mode::AwaitingAck, _) => 100,
+            _ => 0,
+        }
+    }
+
+    /// The queued pomodoro, or the other phase once this one is over.
+    pub fn next_phase(&self) -> Option<String> {
+        if self.next.is_some() {
+            return self.next.clone();
+        }
+
+        match (self.mode, self.phase) {
+            (Mode::Countdown | Mode::Overtime | Mode::AwaitingAck, Phase::Work) => {
+                Some(Phase::Break.name().to_owned())
+            }
+            (Mode::Countdown | Mode::Overtime | Mode::AwaitingAck, Phase::Break) => {
+                Some(Phase::Work.name().to_owned())
+            }
+            _ => None,
+        }
+    }
+
+    /// Time left of a pomodoro, nothing for a stopwatch.
+    pub fn remaining(&self) -> Option<u32> {
+        match self.mode {
+            Mode::Countdown => Some(self.seconds),
+            Mode::Overtime | Mode::AwaitingAck => Some(0),
+            Mode::Idle | Mode::Stopwatch => None,
+        }
+    }
+
+    /// Position of the current pomodoro among today's, counting completed ones
+    /// while on a break.
+    pub fn cycle(&self) -> u32 {
+        match (self.mode, self.phase) {
+            (Mode::Countdown | Mode::Overtime | Mode::AwaitingAck, Phase::Work)
+                if !self.is_idle() =>
+            {
+                self.pomodoros + 1
+            }
+            _ => self.pomodoros,
+        }
+    }
+
+    /// `MM:SS`, with hours when there are any, `+` in overtime.
+    pub fn clock(&self) -> String {
+        match self.mode {
+            Mode::Overtime => format!("+{}", clock(self.seconds)),
+            _ => clock(self.seconds),
+        }
+    }
+
+    /// What `pdoro -r` prints, nothing when idle. The clock alone, as `SS`,
+    /// `MM:SS` or `HH:MM:SS`, a template adds the rest.
+    pub fn summary(&self) -> Option<String> {
+        if self.is_idle() {
+            return None;
+        }
+
+        let clock = Time::get_clock_from_seconds(&self.seconds);
+
+        Some(match (self.mode, self.paused) {
+            (Mode::Overtime, _) => format!("+{}", &clock),
+            (Mode::AwaitingAck, _) => "Pomodoro finished, awaiting acknowledgement.".to_owned(),
+            (_, true) => format!("{} (paused)", &clock),
+            _ => clock,
+        })
+    }
+
+    /// Clock and task on one line, empty when idle.
+    pub fn text(&self) -> String {
+        match (self.is_idle(), &self.labels.task) {
+            (true, _) => String::new(),
+            (false, Some(task)) => format!("{} {}", self.clock(), task),
+            (false, None) => self.clock(),
+        }
+    }
+
+    fn tooltip(&self) -> String {
+        if self.is_idle() {
+            let mut lines = vec!["No pomodoro timer is running.".to_owned()];
+            lines.extend(self.next.iter().map(|next| format!("Next: {}", next)));
+            lines.extend(self.goal.iter().map(|goal| format!("Goal: {}", goal)));
+            return lines.join("\n");
+        }
+
+        let title = match (self.mode, self.phase) {
+            (Mode::Stopwatch, _) => "Stopwatch",
+            (_, Phase::Work) => "Pomodoro",
+            (_, Phase::Break) => "Break",
+        };
+
+        let title = match self.paused {
+            true => format!("{} (paused)", title),
+            false => title.to_owned(),
+        };
+
+        let mut lines = vec![match self.labels.is_empty() {
+            true => title,
+            false => format!("{}: {}", title, self.labels),
+        }];
+
+        lines.extend(self.next_phase().map(|next| format!("Next: {}", next)));
+        lines.extend(self.goal.iter().map(|goal| format!("Goal: {}", goal)));
+        lines.join("\n")
+    }
+}
+
+/// `MM:SS`, or `H:MM:SS` once there are hours.
+pub fn clock(seconds: u32) -> String {
+    match (seconds / 3600, seconds / 60 % 60, seconds % 60) {
+        (0, minutes, seconds) => format!("{:02}:{:02}", minutes, seconds),
+        (hours, minutes, seconds) => format!("{}:{:02}:{:02}", hours, minutes, seconds),
+    }
+}
+
+/// Age past which the status file of a running timer is left over from a
+/// stopped server, the counter rewrites it every second.
+const STALE_AFTER: Duration = Duration::from_secs(5);
+
+/// The timer saved in the status file, none when it is missing, unreadable
+/// or stale.
+fn read_status_file(path: &Path) -> Option<Status> {
+    let age = fs::metadata(path)
+        .ok()?
+        .modified()
+        .ok()?
+        .elapsed()
+        .unwrap_or_default();
+    let content = fs::read(path).ok()?;
+    let status = Status::try_from(&Response::try_from(content.as_slice()).ok()?).ok()?;
+
+    match status.is_idle() || age <= STALE_AFTER {
+        true => Some(status),
+        false => None,
+    }
+}
+
+/// Prints what `pdoro -r` would, from the status file instead of asking the
+/// server. Nothing at all is printed when idle or the server is down, so it
+/// suits PS1.
+pub fn prompt(template: Option<&str>) {
+    let config = Config::load().unwrap_or_default();
+
+    let template = match template
+        .or(config.status_template())
+        .map(Template::from_str)
+    {
+        Some(Ok(template)) => Some(template),
+        Some(Err(e)) => return stderr(e.as_str()),
+        None => None,
+    };
+
+    let status = match read_status_file(&config.status_file_path()) {
+        Some(status) if !status.is_idle() => status,
+        _ => return,
+    };
+
+    match template {
+        Some(template) => stdout(template.render(&status).as_str()),
+        None => stdout(status.summary().unwrap_or_default().as_str()),
+    }
+}
+
+/// Escapes text for Pango markup, which waybar renders labels and tooltips with.
+fn pango(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Prints the timer the way a status bar expects it, idle when the server
+/// is down so the bar keeps working. A template, given or from the config,
+/// replaces the text and prints nothing when idle.
+pub fn status(format: StatusFormat, template: Option<&str>) {
+    let config = Config::load().unwrap_or_default();
+
+    let template = match template
+        .or(config.status_template())
+        .map(Template::from_str)
+    {
+        Some(Ok(template)) => Some(template),
+        Some(Err(e)) => return stderr(e.as_str()),
+        None => None,
+    };
+
+    let (status, tooltip) = match Status::fetch() {
+        Ok(status) => {
+            let tooltip = status.tooltip();
+            (status, tooltip)
+        }
+        Err(e) if matches!(format, StatusFormat::Plain) => return stderr(e.as_str()),
+        Err(e) => (Status::default(), e),
+    };
+
+    let state = status.state();
+    let color = config.status_color(state);
+
+    let text = match (&template, status.is_idle()) {
+        (Some(_), true) => String::new(),
+        (Some(template), false) => template.render(&status),
+        (None, _) => status.text(),
+    };
+
+    let output = match (format, &template) {
+        // nothing at all, so a prompt stays clean
+        (StatusFormat::Plain, Some(_)) if text.is_empty() => return,
+        (StatusFormat::Plain, Some(_)) => text,
+        (StatusFormat::Plain, None) => status
+            .summary()
+            .unwrap_or("No pomodoro timer is running.".to_owned()),
+        (StatusFormat::Waybar, _) => json!({
+            "text": pango(&text),
+            "alt": status.mode.name(),
+            "tooltip": pango(&tooltip),
+            "class": state,
+            "percentage": status.percentage(),
+        })
+        .to_string(),
+        // full text, short text and color lines
+        (StatusFormat::I3blocks, _) => format!(
+            "{}\n{}\n{}",
+            text,
+            match status.is_idle() {
+                true => String::new(),
+                false => status.clock(),
+            },
+            color.unwrap_or_default()
+        ),
+        // polybar has no escape for its format tags, so `%{` in a task is broken up
+        (StatusFormat::Polybar, _) => match (text.replace("%{", "% {"), color) {
+            (text, _) if text.is_empty() => text,
+            (text, Some(color)) => format!("%{{F{}}}{}%{{F-}}", color, text),
+            (text, None) => text,
+        },
+        (StatusFormat::Tmux, _) => match (text.replace('#', "##"), color) {
+            (text, _) if text.is_empty() => text,
+            (text, Some(color)) => format!("#[fg={}]{}#[default]", color, text),
+            (text, None) => text,
+        },
+    };
+
+    stdout(output.as_str())
+}
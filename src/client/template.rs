@@ -0,0 +1,257 @@
+use std::str::FromStr;
+
+use super::status::{clock, Status};
+
+static VARIABLES: [&str; 20] = [
+    "clock",
+    "remaining",
+    "elapsed",
+    "total",
+    "hh",
+    "mm",
+    "ss",
+    "state",
+    "mode",
+    "phase",
+    "task",
+    "tags",
+    "labels",
+    "interruptions",
+    "cycle",
+    "pomodoros",
+    "goal",
+    "next",
+    "percent",
+    "progress_bar",
+];
+
+enum Part {
+    Text(String),
+    /// name and width of a `{name}` or `{name:width}` placeholder
+    Variable(&'static str, Option<usize>),
+}
+
+/// Status text with `{variable}` placeholders, `{{` and `}}` are literal braces.
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+impl FromStr for Template {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let mut parts = vec![];
+        let mut text = String::new();
+        let mut chars = input.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '{' => {
+                    let mut placeholder = String::new();
+
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => placeholder.push(c),
+                            None => {
+                                return Err(format!(
+                                    "Invalid template: unclosed '{{{}'",
+                                    placeholder
+                                ))
+                            }
+                        }
+                    }
+
+                    if !text.is_empty() {
+                        parts.push(Part::Text(std::mem::take(&mut text)));
+                    }
+
+                    parts.push(parse_placeholder(&placeholder)?);
+                }
+                '}' => {
+                    return Err("Invalid template: unmatched '}', write '}}' for a brace".to_owned())
+                }
+                c => text.push(c),
+            }
+        }
+
+        if !text.is_empty() {
+            parts.push(Part::Text(text));
+        }
+
+        Ok(Self { parts })
+    }
+}
+
+fn parse_placeholder(placeholder: &str) -> Result<Part, String> {
+    let (name, width) = match placeholder.split_once(':') {
+        Some((name, width)) => match width.parse::<usize>() {
+            Ok(width) => (name, Some(width)),
+            Err(_) => {
+                return Err(format!(
+                    "Invalid template: bad width in '{{{}}}'",
+                    placeholder
+                ))
+            }
+        },
+        None => (placeholder, None),
+    };
+
+    match VARIABLES.iter().find(|variable| **variable == name) {
+        Some(variable) => Ok(Part::Variable(variable, width)),
+        None => Err(format!(
+            "Invalid template: unknown variable '{}', expected one of {}",
+            name,
+            VARIABLES.join(", ")
+        )),
+    }
+}
+
+impl Template {
+    fn has_hours(&self) -> bool {
+        self.parts
+            .iter()
+            .any(|part| matches!(part, Part::Variable("hh", _)))
+    }
+
+    pub fn render(&self, status: &Status) -> String {
+        self.parts
+            .iter()
+            .map(|part| match part {
+                Part::Text(text) => text.clone(),
+                Part::Variable(name, width) => {
+                    let value = variable(status, name, *width, self.has_hours());
+
+                    match (name, width) {
+                        (&"progress_bar", _) | (_, None) => value,
+                        (_, Some(width)) => format!("{:<width$}", value, width = width),
+                    }
+                }
+            })
+            .collect()
+    }
+}
+
+/// Value of a variable, `width` sets the number of cells of `progress_bar`
+/// and pads every other one. `hours` tells whether the template shows `hh`.
+fn variable(status: &Status, name: &str, width: Option<usize>, hours: bool) -> String {
+    let seconds = status.seconds;
+
+    match name {
+        "clock" => status.clock(),
+        "remaining" => status.remaining().map(clock).unwrap_or_default(),
+        "elapsed" => clock(status.elapsed),
+        "total" => status.total.map(clock).unwrap_or_default(),
+        "hh" => format!("{:02}", seconds / 3600),
+        // whole minutes, so `{mm}:{ss}` keeps counting past an hour
+        "mm" if !hours => format!("{:02}", seconds / 60),
+        "mm" => format!("{:02}", seconds / 60 % 60),
+        "ss" => format!("{:02}", seconds % 60),
+        "state" => status.state().to_owned(),
+        "mode" => status.mode.name().to_owned(),
+        "phase" => status.phase.name().to_owned(),
+        "task" => status.labels.task.clone().unwrap_or_default(),
+        "tags" => status.labels.tags.join(","),
+        "labels" => status.labels.to_string(),
+        "interruptions" => status.interruptions.to_string(),
+        "cycle" => status.cycle().to_string(),
+        "pomodoros" => status.pomodoros.to_string(),
+        "goal" => status.goal.clone().unwrap_or_default(),
+        "next" => status.next_phase().unwrap_or_default(),
+        "percent" => status.percentage().to_string(),
+        "progress_bar" => {
+            let width = width.unwrap_or(10);
+            let filled = width * status.percentage() as usize / 100;
+
+            format!("{}{}", "█".repeat(filled), "░".repeat(width - filled))
+        }
+        _ => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::status::Mode;
+
+    fn render(template: &str, status: &Status) -> String {
+        template.parse::<Template>().unwrap().render(status)
+    }
+
+    fn countdown(seconds: u32, total: u32) -> Status {
+        Status {
+            mode: Mode::Countdown,
+            seconds,
+            total: Some(total),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn renders_variables() {
+        let status = countdown(3665, 7200);
+
+        let cases = [
+            ("{clock}", "1:01:05"),
+            ("{hh}:{mm}:{ss}", "01:01:05"),
+            ("{mm}:{ss}", "61:05"),
+            ("{mm:4}|", "61  |"),
+            ("{phase} {state}", "work running"),
+            ("{{{clock}}}", "{1:01:05}"),
+            ("{{}}", "{}"),
+            ("no variables", "no variables"),
+            ("", ""),
+        ];
+
+        for (template, expected) in cases {
+            assert_eq!(render(template, &status), expected, "{}", template);
+        }
+    }
+
+    #[test]
+    fn renders_progress_bars() {
+        let cases = [
+            ("{progress_bar}", 600, "░░░░░░░░░░"),
+            ("{progress_bar}", 300, "█████░░░░░"),
+            ("{progress_bar:4}", 300, "██░░"),
+            ("{progress_bar:4}", 0, "████"),
+            ("{progress_bar:0}", 300, ""),
+            ("{progress_bar:1}", 1, "░"),
+        ];
+
+        for (template, seconds, expected) in cases {
+            let status = countdown(seconds, 600);
+            assert_eq!(render(template, &status), expected, "{}", template);
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_templates() {
+        let cases = [
+            ("{clock", "unclosed '{clock'"),
+            ("{", "unclosed '{'"),
+            ("clock}", "unmatched '}'"),
+            ("{clock:}", "bad width in '{clock:}'"),
+            ("{clock:-1}", "bad width"),
+            ("{clock:wide}", "bad width"),
+            ("{minutes}", "unknown variable 'minutes'"),
+            ("{}", "unknown variable ''"),
+            ("{CLOCK}", "unknown variable 'CLOCK'"),
+        ];
+
+        for (template, expected) in cases {
+            match template.parse::<Template>() {
+                Ok(_) => panic!("{} should not parse", template),
+                Err(e) => assert!(e.contains(expected), "{}: {}", template, e),
+            }
+        }
+    }
+}
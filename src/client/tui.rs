@@ -0,0 +1,558 @@
+use std::io;
+use std::time::{Duration, Instant};
+
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Alignment, Constraint, Layout, Rect};
+use ratatui::style::{Color, Style};
+use ratatui::text::{Line, Text};
+use ratatui::widgets::{Block, Gauge, List, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+
+use crate::goals::Progress;
+use crate::header::escape;
+use crate::history::{Record, Source};
+use crate::labels::Labels;
+use crate::phase::Phase;
+use crate::time::{Bounds, Time};
+use crate::utils::stderr;
+
+use super::actions::{self, StartOptions, IP};
+use super::reports;
+use super::status::{Mode, Status};
+use super::Client;
+
+static HELP: &str = "s start  b break  w stopwatch  space pause  h halt  a ack  + extend  \
+    i/I interrupt  n note  A queue  N next  d remove  m move  C clear  q quit";
+
+/// How often the timer is asked for.
+const REFRESH: Duration = Duration::from_millis(250);
+/// History, queue and schedules change rarely, so they are read less often.
+const SLOW_REFRESH: Duration = Duration::from_secs(2);
+
+#[derive(Clone, Copy)]
+enum Action {
+    Start(Phase),
+    Extend,
+    Interrupt(Source),
+    Note,
+    QueueAdd,
+    QueueRemove,
+    QueueMove,
+    QueueClear,
+}
+
+/// A line of input a command needs before it is sent.
+struct Prompt {
+    action: Action,
+    label: &'static str,
+    input: String,
+}
+
+impl Prompt {
+    fn new(action: Action) -> Self {
+        let (label, input) = match action {
+            Action::Start(Phase::Work) => ("start (time task)", "25m "),
+            Action::Start(Phase::Break) => ("break (time)", "5m"),
+            Action::Extend => ("extend by", "5m"),
+            Action::Interrupt(Source::External) => ("external interruption (note)", ""),
+            Action::Interrupt(Source::Internal) => ("internal interruption (note)", ""),
+            Action::Note => ("note", ""),
+            Action::QueueAdd => ("queue (time task)", "25m "),
+            Action::QueueRemove => ("remove from queue (position)", "1"),
+            Action::QueueMove => ("move in queue (from to)", ""),
+            Action::QueueClear => ("clear the queue? (y/n)", ""),
+        };
+
+        Self {
+            action,
+            label,
+            input: input.to_owned(),
+        }
+    }
+}
+
+struct Dashboard {
+    /// run when pomodoros started or queued here end
+    callback_with_args: Option<String>,
+    status: Result<Status, String>,
+    /// today's sessions, oldest first
+    sessions: Vec<Record>,
+    progress: Progress,
+    queue: Vec<String>,
+    schedules: Vec<String>,
+    message: Option<String>,
+    prompt: Option<Prompt>,
+}
+
+/// Five rows of a character of the big clock.
+fn glyph(c: char) -> [&'static str; 5] {
+    match c {
+        '0' => ["███", "█ █", "█ █", "█ █", "███"],
+        '1' => ["  █", "  █", "  █", "  █", "  █"],
+        '2' => ["███", "  █", "███", "█  ", "███"],
+        '3' => ["███", "  █", "███", "  █", "███"],
+        '4' => ["█ █", "█ █", "███", "  █", "  █"],
+        '5' => ["███", "█  ", "███", "  █", "███"],
+        '6' => ["███", "█  ", "███", "█ █", "███"],
+        '7' => ["███", "  █", "  █", "  █", "  █"],
+        '8' => ["███", "█ █", "███", "█ █", "███"],
+        '9' => ["███", "█ █", "███", "  █", "███"],
+        ':' => [" ", "█", " ", "█", " "],
+        '+' => ["   ", " █ ", "███", " █ ", "   "],
+        '-' => ["   ", "   ", "███", "   ", "   "],
+        _ => ["   ", "   ", "   ", "   ", "   "],
+    }
+}
+
+fn big_clock(clock: &str) -> Vec<Line<'static>> {
+    (0..5)
+        .map(|row| {
+            let line: Vec<&str> = clock.chars().map(|c| glyph(c)[row]).collect();
+            Line::from(line.join(" "))
+        })
+        .collect()
+}
+
+fn state_color(state: &str) -> Color {
+    match state {
+        "running" => Color::Green,
+        "paused" => Color::Yellow,
+        "break" => Color::Blue,
+        _ => Color::DarkGray,
+    }
+}
+
+/// Sends a request, answering with the daemon's message.
+fn send(request: &str) -> String {
+    match Client::new(IP).run(request) {
+        Ok(res) => res.msg().to_owned(),
+        Err(_) => "Pdoro server has not been started.".to_owned(),
+    }
+}
+
+/// Lines of a listing route, none when the server is down.
+fn list(request: &str) -> Vec<String> {
+    match Client::new(IP).run(request) {
+        Ok(res) if res.status() == 200 => res.msg().lines().map(|l| l.to_owned()).collect(),
+        _ => vec![],
+    }
+}
+
+impl Dashboard {
+    fn new(callback_with_args: Option<String>) -> Self {
+        Self {
+            callback_with_args,
+            status: Status::fetch(),
+            sessions: vec![],
+            progress: Progress::default(),
+            queue: vec![],
+            schedules: vec![],
+            message: None,
+            prompt: None,
+        }
+    }
+
+    fn refresh(&mut self) {
+        self.status = Status::fetch();
+    }
+
+    fn refresh_slow(&mut self) {
+        match reports::load(None) {
+            Ok((config, records, today)) => {
+                self.progress = config.progress(&records, today);
+                self.sessions = records
+                    .into_iter()
+                    .filter(|record| config.day_of(&record.started_at) == today)
+                    .collect();
+                self.sessions.sort_by_key(|record| record.started_at);
+            }
+            Err(e) => self.message = Some(e),
+        }
+
+        self.queue = list("queue-list;");
+        self.schedules = list("schedule-list;");
+    }
+
+    /// Handles a key, false once the dashboard should close.
+    fn key(&mut self, key: KeyEvent) -> bool {
+        if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            return false;
+        }
+
+        if let Some(prompt) = &mut self.prompt {
+            match key.code {
+                KeyCode::Char(c) => prompt.input.push(c),
+                KeyCode::Backspace => {
+                    prompt.input.pop();
+                }
+                KeyCode::Esc => self.prompt = None,
+                KeyCode::Enter => {
+                    let prompt = self.prompt.take().unwrap();
+
+                    self.message = Some(match self.request(prompt.action, &prompt.input) {
+                        Ok(request) => send(&request),
+                        Err(e) => e,
+                    });
+                }
+                _ => {}
+            }
+
+            return true;
+        }
+
+        let request = match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Char(' ') => "pause-resume-counter;",
+            KeyCode::Char('h') => "halt-counter;",
+            KeyCode::Char('a') => "ack;",
+            KeyCode::Char('w') => "stopwatch 0;",
+            KeyCode::Char('N') => "queue-next;",
+            code => {
+                let action = match code {
+                    KeyCode::Char('s') => Action::Start(Phase::Work),
+                    KeyCode::Char('b') => Action::Start(Phase::Break),
+                    KeyCode::Char('+') => Action::Extend,
+                    KeyCode::Char('i') => Action::Interrupt(Source::External),
+                    KeyCode::Char('I') => Action::Interrupt(Source::Internal),
+                    KeyCode::Char('n') => Action::Note,
+                    KeyCode::Char('A') => Action::QueueAdd,
+                    KeyCode::Char('d') => Action::QueueRemove,
+                    KeyCode::Char('m') => Action::QueueMove,
+                    KeyCode::Char('C') => Action::QueueClear,
+                    _ => return true,
+                };
+
+                self.prompt = Some(Prompt::new(action));
+                return true;
+            }
+        };
+
+        self.message = Some(send(request));
+        true
+    }
+
+    /// Request for a prompted command, from what was typed.
+    fn request(&self, action: Action, input: &str) -> Result<String, String> {
+        let input = input.trim();
+        let (time, task) = match input.split_once(' ') {
+            Some((time, task)) => (time, Some(task.trim())),
+            None => (input, None),
+        };
+
+        match action {
+            Action::Start(phase) => actions::start_request(
+                time,
+                &StartOptions {
+                    profile: None,
+                    phase,
+                    overtime: false,
+                    remind_every: None,
+                    max_reminders: None,
+                    warnings: vec![],
+                    force: false,
+                    labels: Labels::new(task, None),
+                    callback_with_args: self.callback_with_args.clone(),
+                },
+            ),
+            Action::Extend => Time::new(input, &Bounds::default())
+                .map(|time| format!("extend {};", time.get_seconds()))
+                .map_err(|e| e.to_string()),
+            Action::Interrupt(source) => match input.is_empty() {
+                true => Ok(format!("interrupt {};", source)),
+                false => Ok(format!("interrupt {}\nnote: {};", source, escape(input))),
+            },
+            Action::Note => match input.is_empty() {
+                true => Err("Empty note, nothing added.".to_owned()),
+                false => Ok(format!("note\ntext: {};", escape(input))),
+            },
+            Action::QueueAdd => actions::queue_add_request(
+                time,
+                &Labels::new(task, None),
+                None,
+                self.callback_with_args.as_deref(),
+            ),
+            Action::QueueRemove => Ok(format!("queue-remove {};", input)),
+            Action::QueueMove => match input.split_whitespace().collect::<Vec<_>>()[..] {
+                [from, to] => Ok(format!("queue-move {} {};", from, to)),
+                _ => Err("Expected two positions, e.g. 3 1".to_owned()),
+            },
+            Action::QueueClear => match input {
+                "y" | "yes" => Ok("queue-clear;".to_owned()),
+                _ => Err("Queue left as is.".to_owned()),
+            },
+        }
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let [top, middle, bottom] = Layout::vertical([
+            Constraint::Length(9),
+            Constraint::Min(4),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+        let [timer, goal] =
+            Layout::horizontal([Constraint::Min(30), Constraint::Length(34)]).areas(top);
+
+        let [sessions, queue, interruptions] = Layout::horizontal([
+            Constraint::Percentage(45),
+            Constraint::Percentage(25),
+            Constraint::Percentage(30),
+        ])
+        .areas(middle);
+
+        self.draw_timer(frame, timer);
+        self.draw_goal(frame, goal);
+        self.draw_sessions(frame, sessions);
+        self.draw_queue(frame, queue);
+        self.draw_interruptions(frame, interruptions);
+
+        let footer = match (&self.prompt, &self.message) {
+            (Some(prompt), _) => format!("{}: {}█", prompt.label, prompt.input),
+            (None, Some(message)) => format!("{}  |  {}", message, HELP),
+            (None, None) => HELP.to_owned(),
+        };
+
+        frame.render_widget(Paragraph::new(footer), bottom);
+    }
+
+    fn draw_timer(&self, frame: &mut Frame, area: Rect) {
+        let block = Block::bordered().title(" Timer ");
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let [clock, info, bar] = Layout::vertical([
+            Constraint::Length(5),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .areas(inner);
+
+        let status = match &self.status {
+            Ok(status) => status,
+            Err(e) => {
+                frame.render_widget(Paragraph::new(big_clock("--:--")).centered(), clock);
+                frame.render_widget(Paragraph::new(e.as_str()).centered(), info);
+                return;
+            }
+        };
+
+        let color = state_color(status.state());
+
+        let (digits, text) = match status.is_idle() {
+            true => (
+                "--:--".to_owned(),
+                match &status.next {
+                    Some(next) => format!("No pomodoro timer is running, next: {}", next),
+                    None => "No pomodoro timer is running.".to_owned(),
+                },
+            ),
+            false => {
+                let mut parts = vec![match status.mode {
+                    Mode::AwaitingAck => "finished, press a to acknowledge".to_owned(),
+                    Mode::Overtime => "overtime".to_owned(),
+                    _ => status.state().to_owned(),
+                }];
+
+                if !status.labels.is_empty() {
+                    parts.push(status.labels.to_string());
+                }
+
+                if let Some(next) = status.next_phase() {
+                    parts.push(format!("next: {}", next));
+                }
+
+                (status.clock(), parts.join(" · "))
+            }
+        };
+
+        frame.render_widget(
+            Paragraph::new(big_clock(&digits))
+                .style(Style::default().fg(color))
+                .alignment(Alignment::Center),
+            clock,
+        );
+        frame.render_widget(Paragraph::new(text).centered(), info);
+
+        let label = match status.mode {
+            Mode::Stopwatch => format!("{} counted", status.clock()),
+            _ => format!("{}%", status.percentage()),
+        };
+
+        frame.render_widget(
+            Gauge::default()
+                .ratio(status.percentage() as f64 / 100.0)
+                .label(label)
+                .gauge_style(Style::default().fg(color)),
+            bar,
+        );
+    }
+
+    fn draw_goal(&self, frame: &mut Frame, area: Rect) {
+        let block = Block::bordered().title(" Goal ");
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let [bar, info] =
+            Layout::vertical([Constraint::Length(1), Constraint::Min(1)]).areas(inner);
+
+        let cycle = match &self.status {
+            Ok(status) if status.cycle() > 0 => format!("pomodoro #{} today", status.cycle()),
+            _ => String::new(),
+        };
+
+        let (ratio, text) = match self.progress.ratio() {
+            Some(ratio) if self.progress.is_reached() => (ratio, "goal reached".to_owned()),
+            Some(ratio) => (ratio, String::new()),
+            None => (0.0, "No daily goal set.".to_owned()),
+        };
+
+        frame.render_widget(
+            Gauge::default()
+                .ratio(ratio)
+                .label(self.progress.to_string())
+                .gauge_style(Style::default().fg(Color::Green)),
+            bar,
+        );
+        frame.render_widget(
+            Paragraph::new(Text::from(vec![text.into(), cycle.into()])),
+            info,
+        );
+    }
+
+    fn draw_sessions(&self, frame: &mut Frame, area: Rect) {
+        let lines: Vec<String> = match self.sessions.is_empty() {
+            true => vec!["No sessions today.".to_owned()],
+            false => self
+                .sessions
+                .iter()
+                .rev()
+                .map(reports::session_line)
+                .collect(),
+        };
+
+        frame.render_widget(
+            List::new(lines).block(Block::bordered().title(" Today ")),
+            area,
+        );
+    }
+
+    fn draw_queue(&self, frame: &mut Frame, area: Rect) {
+        let mut lines = match self.queue.is_empty() {
+            true => vec!["Queue is empty.".to_owned()],
+            false => self.queue.clone(),
+        };
+
+        if !self.schedules.is_empty() {
+            lines.push(String::new());
+            lines.push("Scheduled".to_owned());
+            lines.extend(self.schedules.iter().cloned());
+        }
+
+        frame.render_widget(
+            List::new(lines).block(Block::bordered().title(" Queue ")),
+            area,
+        );
+    }
+
+    fn draw_interruptions(&self, frame: &mut Frame, area: Rect) {
+        let mut lines = vec![];
+
+        match &self.status {
+            Ok(status) if status.interruptions == 1 => {
+                lines.push("1 in the current session".to_owned())
+            }
+            Ok(status) if status.interruptions > 1 => {
+                lines.push(format!("{} in the current session", status.interruptions))
+            }
+            _ => {}
+        }
+
+        lines.extend(self.sessions.iter().rev().flat_map(|record| {
+            record
+                .interruptions
+                .iter()
+                .rev()
+                .map(move |interruption| reports::interruption_line(interruption, record))
+        }));
+
+        if lines.is_empty() {
+            lines.push("No interruptions today.".to_owned());
+        }
+
+        frame.render_widget(
+            List::new(lines).block(Block::bordered().title(" Interruptions ")),
+            area,
+        );
+    }
+}
+
+/// Shows the dashboard until q, only redrawing what changed so it stays
+/// smooth over a slow connection.
+pub fn tui(callback_with_args: Option<&str>) {
+    let terminal = match ratatui::try_init() {
+        Ok(terminal) => terminal,
+        Err(e) => return stderr(format!("Failed to open the dashboard: {}", e).as_str()),
+    };
+
+    let result = run(
+        terminal,
+        Dashboard::new(callback_with_args.map(|c| c.to_owned())),
+    );
+    ratatui::restore();
+
+    if let Err(e) = result {
+        stderr(format!("Failed to draw the dashboard: {}", e).as_str())
+    }
+}
+
+fn run(mut terminal: DefaultTerminal, mut dashboard: Dashboard) -> io::Result<()> {
+    let mut fetched_at = Instant::now();
+    let mut slow_fetched_at = Instant::now();
+    let mut dirty = true;
+
+    dashboard.refresh_slow();
+
+    loop {
+        if fetched_at.elapsed() >= REFRESH {
+            dashboard.refresh();
+            fetched_at = Instant::now();
+            dirty = true;
+        }
+
+        if slow_fetched_at.elapsed() >= SLOW_REFRESH {
+            dashboard.refresh_slow();
+            slow_fetched_at = Instant::now();
+            dirty = true;
+        }
+
+        if dirty {
+            terminal.draw(|frame| dashboard.draw(frame))?;
+            dirty = false;
+        }
+
+        if !event::poll(REFRESH / 5)? {
+            continue;
+        }
+
+        match event::read()? {
+            Event::Key(key) if key.kind == KeyEventKind::Press => {
+                if !dashboard.key(key) {
+                    return Ok(());
+                }
+
+                // unless still typing, show what the command did right away
+                if dashboard.prompt.is_none() {
+                    dashboard.refresh();
+                    dashboard.refresh_slow();
+                    fetched_at = Instant::now();
+                    slow_fetched_at = Instant::now();
+                }
+
+                dirty = true;
+            }
+            Event::Resize(_, _) => dirty = true,
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use std::process;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::args::WaitFor;
+use crate::history::Outcome;
+use crate::time::Time;
+use crate::utils::{stderr, stdout};
+
+use super::status::{Mode, Status};
+
+/// How often the daemon is asked, it only handles one connection at a time
+/// so it is never kept waiting itself.
+const POLL: Duration = Duration::from_millis(250);
+
+/// The current session reached zero, or how it ended, none while it goes on.
+fn outcome(status: &Status, id: u32) -> Result<Option<Outcome>, String> {
+    if let Some((_, outcome)) = status.ended.filter(|(ended, _)| *ended == id) {
+        return Ok(Some(outcome));
+    }
+
+    match status.session {
+        Some(session) if session == id => match status.mode {
+            Mode::Overtime | Mode::AwaitingAck => Ok(Some(Outcome::Completed)),
+            _ => Ok(None),
+        },
+        _ => Err("Lost track of the session, another one ended after it.".to_owned()),
+    }
+}
+
+/// Blocks until the current session completes or is halted. Exits 0 when
+/// it did `until`, 2 when it ended the other way and 3 once `timeout` ran
+/// out, or 1 when nothing runs or the server went away.
+pub fn wait(timeout: Option<&str>, until: WaitFor) {
+    let timeout = match timeout.map(Time::parse) {
+        Some(Ok(time)) => Some(Duration::from_secs(time.get_seconds() as u64)),
+        Some(Err(e)) => return stderr(e.to_string().as_str()),
+        None => None,
+    };
+
+    let started_at = Instant::now();
+
+    let (id, kind) = match Status::fetch() {
+        Ok(status) => match (status.session, status.mode) {
+            (None, _) => return stderr("No pomodoro timer is running."),
+            (Some(_), Mode::Stopwatch) if matches!(until, WaitFor::Complete) => {
+                return stderr("A stopwatch never completes, wait for it with --for halt.")
+            }
+            (Some(id), Mode::Stopwatch) => (id, "Stopwatch"),
+            (Some(id), _) => (id, "Pomodoro"),
+        },
+        Err(e) => return stderr(e.as_str()),
+    };
+
+    let outcome = loop {
+        let status = match Status::fetch() {
+            Ok(status) => status,
+            Err(e) => return stderr(e.as_str()),
+        };
+
+        match outcome(&status, id) {
+            Ok(Some(outcome)) => break outcome,
+            Ok(None) => {}
+            Err(e) => return stderr(e.as_str()),
+        }
+
+        if timeout.is_some_and(|timeout| started_at.elapsed() >= timeout) {
+            eprintln!("Timed out.");
+            process::exit(3);
+        }
+
+        thread::sleep(POLL);
+    };
+
+    let waited_for = matches!(
+        (until, outcome),
+        (WaitFor::Any, _)
+            | (WaitFor::Complete, Outcome::Completed)
+            | (WaitFor::Halt, Outcome::Halted)
+    );
+
+    let msg = match outcome {
+        Outcome::Completed => format!("{} completed.", kind),
+        Outcome::Halted => format!("{} halted.", kind),
+    };
+
+    match waited_for {
+        true => stdout(msg.as_str()),
+        false => {
+            eprintln!("{}", msg);
+            process::exit(2);
+        }
+    }
+}
@@ -0,0 +1,158 @@
+use std::io::{self, Write};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use crossterm::cursor::{Hide, MoveToColumn, MoveToNextLine, MoveUp, Show};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::style::Print;
+use crossterm::terminal::{self, Clear, ClearType};
+use crossterm::{execute, queue};
+
+use crate::time::{Bounds, Time};
+use crate::utils::stderr;
+
+use super::actions::IP;
+use super::status::Status;
+use super::template::Template;
+use super::Client;
+
+static DEFAULT_TEMPLATE: &str = "{state:7} {clock} {progress_bar:20} {percent}% {labels}";
+static HELP: &str = "space pause/resume  q halt  + extend  esc quit";
+
+/// How often the daemon is asked, so changes made elsewhere show up quickly.
+const REFRESH: Duration = Duration::from_millis(250);
+/// How long the answer to a key stays before the help comes back.
+const MESSAGE_FOR: Duration = Duration::from_secs(3);
+
+/// Raw mode without a cursor for as long as it lives.
+struct RawTerminal;
+
+impl RawTerminal {
+    fn enter() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        execute!(io::stdout(), Hide)?;
+        Ok(Self)
+    }
+}
+
+impl Drop for RawTerminal {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), MoveToNextLine(1), Print("\r\n"), Show);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// Redraws the status and message lines in place, in a single write.
+fn render(status: &Result<Status, String>, template: &Template, message: &str) -> io::Result<()> {
+    let line = match status {
+        Ok(status) if status.is_idle() => match &status.next {
+            Some(next) => format!("No pomodoro timer is running, next: {}", next),
+            None => "No pomodoro timer is running.".to_owned(),
+        },
+        Ok(status) => template.render(status),
+        Err(e) => e.clone(),
+    };
+
+    let width = terminal::size()
+        .map(|(width, _)| width as usize)
+        .ok()
+        .filter(|width| *width > 0)
+        .unwrap_or(80);
+    let fit = |text: &str| {
+        text.chars()
+            .take(width.saturating_sub(1))
+            .collect::<String>()
+    };
+
+    let mut stdout = io::stdout();
+
+    queue!(
+        stdout,
+        MoveToColumn(0),
+        Print(fit(&line)),
+        Clear(ClearType::UntilNewLine),
+        Print("\r\n"),
+        Print(fit(message)),
+        Clear(ClearType::UntilNewLine),
+        MoveUp(1),
+        MoveToColumn(0)
+    )?;
+
+    stdout.flush()
+}
+
+/// Sends a request, answering with the daemon's message.
+fn send(request: &str) -> String {
+    match Client::new(IP).run(request) {
+        Ok(res) => res.msg().to_owned(),
+        Err(_) => "Pdoro server has not been started.".to_owned(),
+    }
+}
+
+/// Shows the timer until esc or ctrl-c, space pauses or resumes, q halts and
+/// + adds `extend` to the pomodoro.
+pub fn watch(extend: &str, template: Option<&str>) {
+    let extend_request = match Time::new(extend, &Bounds::default()) {
+        Ok(time) => format!("extend {};", time.get_seconds()),
+        Err(e) => return stderr(e.to_string().as_str()),
+    };
+
+    let template = match Template::from_str(template.unwrap_or(DEFAULT_TEMPLATE)) {
+        Ok(template) => template,
+        Err(e) => return stderr(e.as_str()),
+    };
+
+    if let Err(e) = run(&extend_request, &template) {
+        stderr(format!("Failed to watch the timer: {}", e).as_str())
+    }
+}
+
+fn run(extend_request: &str, template: &Template) -> io::Result<()> {
+    let _terminal = RawTerminal::enter()?;
+    let mut message = HELP.to_owned();
+    let mut message_at = Instant::now();
+    let mut status = Status::fetch();
+    let mut fetched_at = Instant::now();
+
+    render(&status, template, &message)?;
+
+    loop {
+        if message_at.elapsed() >= MESSAGE_FOR {
+            message = HELP.to_owned();
+        }
+
+        if fetched_at.elapsed() >= REFRESH {
+            status = Status::fetch();
+            fetched_at = Instant::now();
+            render(&status, template, &message)?;
+        }
+
+        if !event::poll(REFRESH / 5)? {
+            continue;
+        }
+
+        let key = match event::read()? {
+            Event::Key(key) if key.kind == KeyEventKind::Press => key,
+            Event::Resize(_, _) => {
+                render(&status, template, &message)?;
+                continue;
+            }
+            _ => continue,
+        };
+
+        message = match key.code {
+            KeyCode::Char(' ') => send("pause-resume-counter;"),
+            KeyCode::Char('q') => send("halt-counter;"),
+            KeyCode::Char('+') => send(extend_request),
+            KeyCode::Esc => return Ok(()),
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return Ok(()),
+            _ => continue,
+        };
+
+        // show the outcome right away
+        message_at = Instant::now();
+        status = Status::fetch();
+        fetched_at = Instant::now();
+        render(&status, template, &message)?;
+    }
+}
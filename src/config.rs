@@ -0,0 +1,480 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime};
+use serde::{Deserialize, Deserializer};
+
+use crate::cron::Cron;
+use crate::goals::{Goals, Progress};
+use crate::history::Record;
+use crate::hours::Hours;
+use crate::labels::Labels;
+use crate::phase::Phase;
+use crate::time::{Bounds, Time};
+
+/// Settings read from `config.toml`. Top level keys apply to every profile,
+/// `[profiles.<name>]` tables override them for that profile only.
+#[derive(Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// file finished sessions are appended to
+    history: Option<PathBuf>,
+    queue: QueueSettings,
+    /// file the time schedules were last checked at is kept in
+    schedule_state: Option<PathBuf>,
+    /// file the server keeps the timer in for `pdoro prompt`
+    status_file: Option<PathBuf>,
+    schedules: Vec<Schedule>,
+    hours: Hours,
+    goal: Goals,
+    timesheet: TimesheetSettings,
+    status: StatusSettings,
+    /// local time a day starts at, sessions before it count towards the day before
+    #[serde(deserialize_with = "time_of_day")]
+    day_start: Option<NaiveTime>,
+    #[serde(flatten)]
+    settings: Settings,
+    profiles: HashMap<String, Settings>,
+}
+
+static GLOBAL: OnceLock<Config> = OnceLock::new();
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct Settings {
+    bounds: PhaseBounds,
+    overtime: Option<bool>,
+    ack: AckSettings,
+    #[serde(deserialize_with = "durations")]
+    warnings: Option<Vec<u32>>,
+    warning_callback: Option<String>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct PhaseBounds {
+    work: BoundsSettings,
+    #[serde(rename = "break")]
+    break_: BoundsSettings,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct BoundsSettings {
+    #[serde(deserialize_with = "duration")]
+    min: Option<u32>,
+    #[serde(deserialize_with = "duration")]
+    max: Option<u32>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct QueueSettings {
+    file: Option<PathBuf>,
+    /// start the next queued pomodoro as soon as one completes
+    auto_start: Option<bool>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct TimesheetSettings {
+    /// increment every cell of `pdoro report timesheet` is rounded to
+    #[serde(deserialize_with = "duration")]
+    round: Option<u32>,
+    rounding: Rounding,
+}
+
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Rounding {
+    #[default]
+    Nearest,
+    Up,
+    Down,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct StatusSettings {
+    /// text of `pdoro -r` and `pdoro status` unless given `--template`
+    template: Option<String>,
+    colors: StatusColors,
+}
+
+/// Colors of `pdoro status` per state, an empty one keeps the bar's own.
+#[derive(Deserialize)]
+#[serde(default)]
+struct StatusColors {
+    running: Option<String>,
+    paused: Option<String>,
+    #[serde(rename = "break")]
+    break_: Option<String>,
+    idle: Option<String>,
+}
+
+impl Default for StatusColors {
+    fn default() -> Self {
+        Self {
+            running: None,
+            paused: Some("#e5c07b".to_owned()),
+            break_: Some("#61afef".to_owned()),
+            idle: None,
+        }
+    }
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct AckSettings {
+    #[serde(deserialize_with = "duration")]
+    remind_every: Option<u32>,
+    max_reminders: Option<u32>,
+}
+
+#[derive(Deserialize)]
+struct ScheduleSettings {
+    name: String,
+    cron: Option<String>,
+    at: Option<String>,
+    time: String,
+    #[serde(flatten)]
+    labels: Labels,
+    profile: Option<String>,
+    callback: Option<String>,
+    #[serde(default)]
+    missed: Missed,
+}
+
+/// What to do with runs of a schedule that passed while the server was down.
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Missed {
+    #[default]
+    Skip,
+    /// start once however many runs were missed
+    RunOnce,
+}
+
+pub enum When {
+    Cron(Cron),
+    At(NaiveDateTime),
+}
+
+/// A pomodoro started by the server at a given time or on a recurrence.
+#[derive(Deserialize)]
+#[serde(try_from = "ScheduleSettings")]
+pub struct Schedule {
+    pub name: String,
+    pub when: When,
+    pub seconds: u32,
+    pub labels: Labels,
+    pub profile: Option<String>,
+    pub callback: Option<String>,
+    pub missed: Missed,
+}
+
+impl TryFrom<ScheduleSettings> for Schedule {
+    type Error = String;
+
+    fn try_from(settings: ScheduleSettings) -> Result<Self, Self::Error> {
+        let when = match (&settings.cron, &settings.at) {
+            (Some(cron), None) => When::Cron(cron.parse()?),
+            (None, Some(at)) => When::At(
+                NaiveDateTime::parse_from_str(at, "%Y-%m-%d %H:%M")
+                    .or_else(|_| NaiveDateTime::parse_from_str(at, "%Y-%m-%dT%H:%M"))
+                    .map_err(|_| format!("Invalid date '{}': expected YYYY-MM-DD HH:MM", at))?,
+            ),
+            _ => {
+                return Err(format!(
+                    "Schedule {} needs either `cron` or `at`",
+                    settings.name
+                ))
+            }
+        };
+
+        let seconds = Time::parse(&settings.time)
+            .map_err(|e| e.to_string())?
+            .get_seconds();
+
+        Ok(Self {
+            name: settings.name,
+            when,
+            seconds,
+            labels: settings.labels,
+            profile: settings.profile,
+            callback: settings.callback,
+            missed: settings.missed,
+        })
+    }
+}
+
+impl Schedule {
+    /// First run after `after`, if the schedule has any left.
+    pub fn next_after(&self, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        match &self.when {
+            When::Cron(cron) => cron.next_after(after),
+            When::At(at) => Some(*at).filter(|at| *at > after),
+        }
+    }
+}
+
+/// Settings of a profile merged with the top level ones.
+pub struct Profile {
+    work_bounds: Bounds,
+    break_bounds: Bounds,
+    /// count up after a pomodoro ends until it is acknowledged
+    pub overtime: bool,
+    /// wait for acknowledgement after a pomodoro ends, reminding at this interval
+    pub remind_every: Option<u32>,
+    pub max_reminders: Option<u32>,
+    /// warn this many seconds before a pomodoro ends
+    pub warnings: Vec<u32>,
+    pub warning_callback: Option<String>,
+}
+
+impl Profile {
+    pub fn bounds(&self, phase: Phase) -> &Bounds {
+        match phase {
+            Phase::Work => &self.work_bounds,
+            Phase::Break => &self.break_bounds,
+        }
+    }
+}
+
+impl Config {
+    pub fn load() -> Result<Self, String> {
+        let path = match Self::path() {
+            Some(path) if path.exists() => path,
+            _ => return Ok(Self::default()),
+        };
+
+        let content = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+
+        let config: Self = toml::from_str(&content)
+            .map_err(|e| format!("Invalid config {}: {}", path.display(), e))?;
+
+        config
+            .check_schedules()
+            .map_err(|e| format!("Invalid config {}: {}", path.display(), e))?;
+
+        Ok(config)
+    }
+
+    /// Schedules start pomodoros without a client, so their length is
+    /// checked against the bounds of their profile here.
+    fn check_schedules(&self) -> Result<(), String> {
+        for schedule in &self.schedules {
+            self.profile(schedule.profile.as_deref())?
+                .bounds(Phase::Work)
+                .check(schedule.seconds)
+                .map_err(|e| format!("schedule {}: {}", schedule.name, e))?;
+        }
+
+        Ok(())
+    }
+
+    /// Config the server was started with, set once before daemonizing.
+    pub fn global() -> &'static Self {
+        GLOBAL.get_or_init(Self::default)
+    }
+
+    pub fn set_global(config: Self) {
+        if GLOBAL.set(config).is_err() {
+            println!("Config already set.")
+        }
+    }
+
+    pub fn history_path(&self) -> PathBuf {
+        self.history
+            .clone()
+            .unwrap_or(PathBuf::from("/tmp/pdoro.history"))
+    }
+
+    pub fn queue_path(&self) -> PathBuf {
+        self.queue
+            .file
+            .clone()
+            .unwrap_or(PathBuf::from("/tmp/pdoro.queue"))
+    }
+
+    pub fn queue_auto_start(&self) -> bool {
+        self.queue.auto_start.unwrap_or(true)
+    }
+
+    pub fn schedules(&self) -> &[Schedule] {
+        &self.schedules
+    }
+
+    pub fn hours(&self) -> &Hours {
+        &self.hours
+    }
+
+    /// Day `time` counts towards, which starts at `day_start` rather than midnight.
+    pub fn day_of(&self, time: &DateTime<Local>) -> NaiveDate {
+        let since_midnight = self
+            .day_start
+            .map(|start| start - NaiveTime::MIN)
+            .unwrap_or(Duration::zero());
+
+        (time.naive_local() - since_midnight).date()
+    }
+
+    /// Progress of `day` towards its goal, counting the sessions started that day.
+    pub fn progress(&self, records: &[Record], day: NaiveDate) -> Progress {
+        let mut progress = Progress {
+            target: self.goal.target(day),
+            ..Default::default()
+        };
+
+        for record in records
+            .iter()
+            .filter(|record| self.day_of(&record.started_at) == day)
+        {
+            progress.add(record);
+        }
+
+        progress
+    }
+
+    /// Seconds timesheet cells are rounded to, zero for no rounding.
+    pub fn timesheet_round(&self) -> u32 {
+        self.timesheet.round.unwrap_or(0)
+    }
+
+    pub fn timesheet_rounding(&self) -> Rounding {
+        self.timesheet.rounding
+    }
+
+    pub fn status_template(&self) -> Option<&str> {
+        self.status.template.as_deref()
+    }
+
+    pub fn status_color(&self, state: &str) -> Option<&str> {
+        let colors = &self.status.colors;
+
+        match state {
+            "running" => colors.running.as_deref(),
+            "paused" => colors.paused.as_deref(),
+            "break" => colors.break_.as_deref(),
+            _ => colors.idle.as_deref(),
+        }
+        .filter(|color| !color.is_empty())
+    }
+
+    pub fn schedule_state_path(&self) -> PathBuf {
+        self.schedule_state
+            .clone()
+            .unwrap_or(PathBuf::from("/tmp/pdoro.schedule"))
+    }
+
+    pub fn status_file_path(&self) -> PathBuf {
+        self.status_file
+            .clone()
+            .unwrap_or(PathBuf::from("/tmp/pdoro.status"))
+    }
+
+    fn path() -> Option<PathBuf> {
+        if let Ok(path) = env::var("PDORO_CONFIG") {
+            return Some(PathBuf::from(path));
+        }
+
+        env::var("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|_| env::var("HOME").map(|home| PathBuf::from(home).join(".config")))
+            .ok()
+            .map(|dir| dir.join("pdoro").join("config.toml"))
+    }
+
+    pub fn profile(&self, name: Option<&str>) -> Result<Profile, String> {
+        let overrides = match name {
+            Some(name) => Some(
+                self.profiles
+                    .get(name)
+                    .ok_or(format!("Unknown profile: {}", name))?,
+            ),
+            None => None,
+        };
+
+        let bounds = |select: fn(&Settings) -> &BoundsSettings| -> Result<Bounds, String> {
+            let global = select(&self.settings);
+            let profile = overrides.map(select);
+            let default = Bounds::default();
+
+            let bounds = Bounds {
+                min: profile
+                    .and_then(|p| p.min)
+                    .or(global.min)
+                    .unwrap_or(default.min),
+                max: profile
+                    .and_then(|p| p.max)
+                    .or(global.max)
+                    .unwrap_or(default.max),
+            };
+
+            match bounds.min <= bounds.max {
+                true => Ok(bounds),
+                false => Err("Invalid config: bounds min is greater than max".to_string()),
+            }
+        };
+
+        Ok(Profile {
+            work_bounds: bounds(|s| &s.bounds.work)?,
+            break_bounds: bounds(|s| &s.bounds.break_)?,
+            overtime: overrides
+                .and_then(|p| p.overtime)
+                .or(self.settings.overtime)
+                .unwrap_or(false),
+            remind_every: overrides
+                .and_then(|p| p.ack.remind_every)
+                .or(self.settings.ack.remind_every),
+            max_reminders: overrides
+                .and_then(|p| p.ack.max_reminders)
+                .or(self.settings.ack.max_reminders),
+            warnings: overrides
+                .and_then(|p| p.warnings.clone())
+                .or(self.settings.warnings.clone())
+                .unwrap_or_default(),
+            warning_callback: overrides
+                .and_then(|p| p.warning_callback.clone())
+                .or(self.settings.warning_callback.clone()),
+        })
+    }
+}
+
+pub(crate) fn duration<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<u32>, D::Error> {
+    let input = String::deserialize(deserializer)?;
+
+    match Time::parse(&input) {
+        Ok(time) => Ok(Some(time.get_seconds())),
+        Err(e) => Err(serde::de::Error::custom(e)),
+    }
+}
+
+fn durations<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Vec<u32>>, D::Error> {
+    Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|input| match Time::parse(input) {
+            Ok(time) => Ok(time.get_seconds()),
+            Err(e) => Err(serde::de::Error::custom(e)),
+        })
+        .collect::<Result<_, _>>()
+        .map(Some)
+}
+
+fn time_of_day<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<NaiveTime>, D::Error> {
+    let input = String::deserialize(deserializer)?;
+
+    match NaiveTime::parse_from_str(&input, "%H:%M") {
+        Ok(time) => Ok(Some(time)),
+        Err(_) => Err(serde::de::Error::custom(format!(
+            "invalid time '{}', expected HH:MM",
+            input
+        ))),
+    }
+}
@@ -0,0 +1,228 @@
+use chrono::{Datelike, Duration, NaiveDateTime, Timelike};
+use std::str::FromStr;
+
+/// Values a cron field matches, as bits, and whether it was `*`.
+struct Field {
+    bits: u64,
+    any: bool,
+}
+
+impl Field {
+    fn parse(input: &str, min: u32, max: u32) -> Result<Self, String> {
+        let mut bits = 0;
+
+        for part in input.split(',') {
+            let (range, step) = match part.split_once('/') {
+                Some((range, step)) => match step.parse::<u32>() {
+                    Ok(step) if step > 0 => (range, step),
+                    _ => return Err(format!("invalid step '{}'", step)),
+                },
+                None => (part, 1),
+            };
+
+            let (start, end) = match range.split_once('-') {
+                _ if range == "*" => (min, max),
+                Some((start, end)) => (
+                    Self::parse_value(start, min, max)?,
+                    Self::parse_value(end, min, max)?,
+                ),
+                // `5/15` counts from 5 to the end of the field
+                None if step > 1 => (Self::parse_value(range, min, max)?, max),
+                None => {
+                    let value = Self::parse_value(range, min, max)?;
+                    (value, value)
+                }
+            };
+
+            if start > end {
+                return Err(format!("invalid range '{}'", range));
+            }
+
+            for value in (start..=end).step_by(step as usize) {
+                bits |= 1 << value;
+            }
+        }
+
+        Ok(Self {
+            bits,
+            any: input == "*",
+        })
+    }
+
+    fn parse_value(input: &str, min: u32, max: u32) -> Result<u32, String> {
+        match input.parse::<u32>() {
+            Ok(value) if value >= min && value <= max => Ok(value),
+            _ => Err(format!(
+                "invalid value '{}', expected {} to {}",
+                input, min, max
+            )),
+        }
+    }
+
+    fn has(&self, value: u32) -> bool {
+        self.bits & (1 << value) != 0
+    }
+}
+
+/// Five field cron expression: minute, hour, day of month, month and day of
+/// week (0 or 7 is sunday). Fields are `*`, a number, a range `1-5`, a list
+/// `1,3` or a step `*/15`, evaluated in local time.
+pub struct Cron {
+    minutes: Field,
+    hours: Field,
+    days: Field,
+    months: Field,
+    weekdays: Field,
+}
+
+impl FromStr for Cron {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<&str> = input.split_whitespace().collect();
+
+        let [minutes, hours, days, months, weekdays] = fields[..] else {
+            return Err(format!(
+                "Invalid cron expression '{}': expected 5 fields",
+                input
+            ));
+        };
+
+        let to_error = |e| format!("Invalid cron expression '{}': {}", input, e);
+
+        let mut weekdays = Field::parse(weekdays, 0, 7).map_err(to_error)?;
+
+        if weekdays.has(7) {
+            weekdays.bits |= 1;
+        }
+
+        Ok(Self {
+            minutes: Field::parse(minutes, 0, 59).map_err(to_error)?,
+            hours: Field::parse(hours, 0, 23).map_err(to_error)?,
+            days: Field::parse(days, 1, 31).map_err(to_error)?,
+            months: Field::parse(months, 1, 12).map_err(to_error)?,
+            weekdays,
+        })
+    }
+}
+
+impl Cron {
+    /// Like cron, a day matches either field when both day fields are restricted.
+    fn matches_day(&self, time: &NaiveDateTime) -> bool {
+        let day = self.days.has(time.day());
+        let weekday = self.weekdays.has(time.weekday().num_days_from_sunday());
+
+        self.months.has(time.month())
+            && match (self.days.any, self.weekdays.any) {
+                (true, true) => true,
+                (true, false) => weekday,
+                (false, true) => day,
+                (false, false) => day || weekday,
+            }
+    }
+
+    /// First matching minute after `after`, looking up to five years ahead.
+    pub fn next_after(&self, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        let mut time = after.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        let limit = time + Duration::days(5 * 366);
+
+        while time < limit {
+            if !self.matches_day(&time) {
+                time = time.date().succ_opt()?.and_hms_opt(0, 0, 0)?;
+            } else if !self.hours.has(time.hour()) {
+                time = time.with_minute(0)? + Duration::hours(1);
+            } else if !self.minutes.has(time.minute()) {
+                time += Duration::minutes(1);
+            } else {
+                return Some(time);
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(input: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(input, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn next(cron: &str, after: &str) -> Option<NaiveDateTime> {
+        cron.parse::<Cron>().unwrap().next_after(at(after))
+    }
+
+    #[test]
+    fn parses_valid_expressions() {
+        let cases = [
+            "* * * * *",
+            "0 9 * * 1-5",
+            "*/15 * * * *",
+            "5/15 * * * *",
+            "0,30 8-18/2 * * *",
+            "59 23 31 12 7",
+            "0 0 1 1 0",
+            "  0   9 * * *  ",
+        ];
+
+        for input in cases {
+            assert!(input.parse::<Cron>().is_ok(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_expressions() {
+        let cases = [
+            "",
+            "* * * *",
+            "* * * * * *",
+            "*/0 * * * *",
+            "*/ * * * *",
+            "*/-1 * * * *",
+            "60 * * * *",
+            "* 24 * * *",
+            "* * 0 * *",
+            "* * 32 * *",
+            "* * * 0 *",
+            "* * * 13 *",
+            "* * * * 8",
+            "5-1 * * * *",
+            "1- * * * *",
+            "a * * * *",
+            "1,,2 * * * *",
+        ];
+
+        for input in cases {
+            assert!(input.parse::<Cron>().is_err(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn finds_the_next_run() {
+        // 2026-10-19 is a monday
+        let cases = [
+            ("0 9 * * *", "2026-10-19 08:30", "2026-10-19 09:00"),
+            ("0 9 * * *", "2026-10-19 09:00", "2026-10-20 09:00"),
+            ("*/15 * * * *", "2026-10-19 10:07", "2026-10-19 10:15"),
+            ("5/15 * * * *", "2026-10-19 10:21", "2026-10-19 10:35"),
+            ("0 9 * * 1-5", "2026-10-23 10:00", "2026-10-26 09:00"),
+            ("0 9 * * 0", "2026-10-19 10:00", "2026-10-25 09:00"),
+            ("0 9 * * 7", "2026-10-19 10:00", "2026-10-25 09:00"),
+            ("0 0 1 * *", "2026-10-19 10:00", "2026-11-01 00:00"),
+            ("30 23 31 12 *", "2026-10-19 10:00", "2026-12-31 23:30"),
+            // either day field matches when both are restricted
+            ("0 9 1 * 1", "2026-10-19 10:00", "2026-10-26 09:00"),
+        ];
+
+        for (cron, after, expected) in cases {
+            assert_eq!(next(cron, after), Some(at(expected)), "{}", cron);
+        }
+    }
+
+    #[test]
+    fn gives_up_on_impossible_dates() {
+        assert_eq!(next("0 0 30 2 *", "2026-10-19 10:00"), None);
+    }
+}
@@ -0,0 +1,191 @@
+use chrono::{Datelike, NaiveDate, Weekday};
+use serde::Deserialize;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use crate::history::{Kind, Outcome, Record};
+use crate::phase::Phase;
+use crate::time::Time;
+
+/// What a day should add up to, either or both of them.
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(default)]
+pub struct Target {
+    /// completed pomodoros
+    pomodoros: Option<u32>,
+    /// seconds counted by pomodoros and stopwatches
+    #[serde(deserialize_with = "crate::config::duration")]
+    focus: Option<u32>,
+}
+
+impl Target {
+    pub fn is_empty(&self) -> bool {
+        self.pomodoros.is_none() && self.focus.is_none()
+    }
+}
+
+/// Daily target, `[goal.<weekday>]` tables override the top level one for
+/// that day.
+#[derive(Default, Deserialize)]
+#[serde(default)]
+pub struct Goals {
+    #[serde(flatten)]
+    every_day: Target,
+    mon: Target,
+    tue: Target,
+    wed: Target,
+    thu: Target,
+    fri: Target,
+    sat: Target,
+    sun: Target,
+}
+
+impl Goals {
+    pub fn target(&self, day: NaiveDate) -> Target {
+        let target = match day.weekday() {
+            Weekday::Mon => self.mon,
+            Weekday::Tue => self.tue,
+            Weekday::Wed => self.wed,
+            Weekday::Thu => self.thu,
+            Weekday::Fri => self.fri,
+            Weekday::Sat => self.sat,
+            Weekday::Sun => self.sun,
+        };
+
+        Target {
+            pomodoros: target.pomodoros.or(self.every_day.pomodoros),
+            focus: target.focus.or(self.every_day.focus),
+        }
+    }
+}
+
+/// How far a day got towards its target.
+#[derive(Clone, Copy, Default)]
+pub struct Progress {
+    pub pomodoros: u32,
+    pub focus: u32,
+    pub target: Target,
+}
+
+impl Display for Progress {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        let mut parts = vec![];
+
+        if let Some(pomodoros) = self.target.pomodoros {
+            parts.push(format!("{}/{}", self.pomodoros, pomodoros));
+        }
+
+        if let Some(focus) = self.target.focus {
+            parts.push(format!(
+                "{}/{}",
+                Time::get_duration_from_seconds(&self.focus),
+                Time::get_duration_from_seconds(&focus)
+            ));
+        }
+
+        write!(f, "{}", parts.join(" "))
+    }
+}
+
+impl Progress {
+    /// Counts a record, breaks count towards neither part.
+    pub fn add(&mut self, record: &Record) {
+        if record.phase == Phase::Break {
+            return;
+        }
+
+        if let (Kind::Pomodoro, Outcome::Completed) = (record.kind, record.outcome) {
+            self.pomodoros += 1;
+        }
+
+        self.focus += record.elapsed;
+    }
+
+    /// Share of the target done, that of the part furthest behind.
+    pub fn ratio(&self) -> Option<f64> {
+        let parts = [
+            self.target.pomodoros.map(|p| (self.pomodoros, p)),
+            self.target.focus.map(|f| (self.focus, f)),
+        ];
+
+        parts
+            .iter()
+            .flatten()
+            .map(|(done, target)| match target {
+                0 => 1.0,
+                target => (*done as f64 / *target as f64).min(1.0),
+            })
+            .reduce(f64::min)
+    }
+
+    /// Reached once every part of a non empty target is.
+    pub fn is_reached(&self) -> bool {
+        !self.target.is_empty()
+            && self.target.pomodoros.is_none_or(|p| self.pomodoros >= p)
+            && self.target.focus.is_none_or(|f| self.focus >= f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::labels::Labels;
+    use chrono::Local;
+
+    fn record(kind: Kind, phase: Phase, outcome: Outcome, elapsed: u32) -> Record {
+        Record {
+            kind,
+            phase,
+            started_at: Local::now(),
+            ended_at: Local::now(),
+            duration: Some(elapsed),
+            elapsed,
+            paused: 0,
+            overtime: 0,
+            labels: Labels::default(),
+            interruptions: vec![],
+            notes: vec![],
+            outcome,
+        }
+    }
+
+    #[test]
+    fn counts_work_but_not_breaks() {
+        let cases = [
+            (
+                record(Kind::Pomodoro, Phase::Work, Outcome::Completed, 1500),
+                (1, 1500),
+            ),
+            (
+                record(Kind::Pomodoro, Phase::Work, Outcome::Halted, 600),
+                (0, 600),
+            ),
+            (
+                record(Kind::Stopwatch, Phase::Work, Outcome::Completed, 900),
+                (0, 900),
+            ),
+            (
+                record(Kind::Pomodoro, Phase::Break, Outcome::Completed, 300),
+                (0, 0),
+            ),
+            (
+                record(Kind::Pomodoro, Phase::Break, Outcome::Halted, 120),
+                (0, 0),
+            ),
+        ];
+
+        for (record, (pomodoros, focus)) in cases {
+            let mut progress = Progress::default();
+            progress.add(&record);
+
+            assert_eq!((progress.pomodoros, progress.focus), (pomodoros, focus));
+        }
+    }
+
+    #[test]
+    fn reads_history_without_phase_as_work() {
+        let line = r#"{"kind":"pomodoro","started_at":"2026-10-19T09:00:00+02:00","ended_at":"2026-10-19T09:25:00+02:00","duration":1500,"elapsed":1500,"paused":0,"outcome":"completed"}"#;
+        let record: Record = serde_json::from_str(line).unwrap();
+
+        assert_eq!(record.phase, Phase::Work);
+    }
+}
@@ -0,0 +1,77 @@
+/// Escapes a free form header value, so line breaks and `;` in it cannot
+/// end its line or the whole request early.
+pub fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+
+    for c in value.chars() {
+        match c {
+            '%' => escaped.push_str("%25"),
+            '\n' => escaped.push_str("%0A"),
+            '\r' => escaped.push_str("%0D"),
+            ';' => escaped.push_str("%3B"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+/// Reverts `escape`, any other `%` is kept as it is.
+pub fn unescape(value: &str) -> String {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut rest = value;
+
+    while let Some(i) = rest.find('%') {
+        unescaped.push_str(&rest[..i]);
+
+        let c = match rest.get(i + 1..i + 3) {
+            Some("25") => '%',
+            Some("0A") => '\n',
+            Some("0D") => '\r',
+            Some("3B") => ';',
+            _ => {
+                unescaped.push('%');
+                rest = &rest[i + 1..];
+                continue;
+            }
+        };
+
+        unescaped.push(c);
+        rest = &rest[i + 3..];
+    }
+
+    unescaped.push_str(rest);
+    unescaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips() {
+        let cases = [
+            "",
+            "plain task",
+            "two\nlines",
+            "ends;\nheader: injected",
+            "100% done",
+            "%0A literally",
+            "crlf\r\n",
+        ];
+
+        for value in cases {
+            let escaped = escape(value);
+
+            assert!(!escaped.contains(['\n', '\r', ';']), "{}", value);
+            assert_eq!(unescape(&escaped), value);
+        }
+    }
+
+    #[test]
+    fn keeps_unknown_escapes() {
+        assert_eq!(unescape("date +%H:%M"), "date +%H:%M");
+        assert_eq!(unescape("50%"), "50%");
+        assert_eq!(unescape("%%3B"), "%;");
+    }
+}
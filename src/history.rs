@@ -0,0 +1,184 @@
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Result as IoResult, Write};
+use std::path::Path;
+use std::sync::Mutex;
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::labels::Labels;
+use crate::phase::Phase;
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Kind {
+    Pomodoro,
+    Stopwatch,
+}
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome {
+    Completed,
+    Halted,
+}
+
+/// Whether an interruption came from someone else or from oneself.
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Source {
+    #[default]
+    External,
+    Internal,
+}
+
+impl Display for Source {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self {
+            Self::External => f.pad("external"),
+            Self::Internal => f.pad("internal"),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Interruption {
+    pub at: DateTime<Local>,
+    pub source: Source,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+/// A finished session, stored as one JSON object per line.
+#[derive(Serialize, Deserialize)]
+pub struct Record {
+    pub kind: Kind,
+    /// work for history written before breaks were recorded apart
+    #[serde(default)]
+    pub phase: Phase,
+    pub started_at: DateTime<Local>,
+    pub ended_at: DateTime<Local>,
+    /// planned length in seconds, stopwatches have none
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration: Option<u32>,
+    /// seconds the counter was running
+    pub elapsed: u32,
+    /// seconds the counter was paused
+    pub paused: u32,
+    /// seconds spent past the end of the session
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub overtime: u32,
+    #[serde(flatten)]
+    pub labels: Labels,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub interruptions: Vec<Interruption>,
+    /// what got done, added with `pdoro note`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<String>,
+    pub outcome: Outcome,
+}
+
+fn is_zero(seconds: &u32) -> bool {
+    *seconds == 0
+}
+
+/// Keeps an append from landing between the read and the rewrite of
+/// `amend_last`, which would drop it.
+static WRITING: Mutex<()> = Mutex::new(());
+
+pub fn append(path: &Path, record: &Record) -> IoResult<()> {
+    let _writing = WRITING.lock().unwrap();
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+
+    writeln!(file, "{}", serde_json::to_string(record)?)
+}
+
+/// Appends the records not starting at the same second as one already
+/// recorded, or as one before them, and returns how many were appended.
+pub fn append_missing(path: &Path, records: &[Record]) -> Result<usize, String> {
+    let _writing = WRITING.lock().unwrap();
+    let to_error = |e: String| format!("Failed to write {}: {}", path.display(), e);
+
+    let mut starts: HashSet<i64> = read(path)?
+        .iter()
+        .map(|record| record.started_at.timestamp())
+        .collect();
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| to_error(e.to_string()))?;
+
+    let mut appended = 0;
+
+    for record in records {
+        if starts.insert(record.started_at.timestamp()) {
+            let line = serde_json::to_string(record).map_err(|e| to_error(e.to_string()))?;
+            writeln!(file, "{}", line).map_err(|e| to_error(e.to_string()))?;
+            appended += 1;
+        }
+    }
+
+    Ok(appended)
+}
+
+/// Reads every record, a missing file is an empty history.
+pub fn read(path: &Path) -> Result<Vec<Record>, String> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(vec![]),
+        Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+    };
+
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            serde_json::from_str(line).map_err(|e| {
+                format!(
+                    "Invalid record on line {} of {}: {}",
+                    i + 1,
+                    path.display(),
+                    e
+                )
+            })
+        })
+        .collect()
+}
+
+/// Changes the last record in place, returns false when there is none.
+pub fn amend_last(path: &Path, amend: impl FnOnce(&mut Record)) -> Result<bool, String> {
+    let _writing = WRITING.lock().unwrap();
+    let mut records = read(path)?;
+
+    let record = match records.last_mut() {
+        Some(record) => record,
+        None => return Ok(false),
+    };
+
+    amend(record);
+
+    let mut content = String::new();
+
+    for record in &records {
+        content.push_str(
+            serde_json::to_string(record)
+                .map_err(|e| e.to_string())?
+                .as_str(),
+        );
+        content.push('\n');
+    }
+
+    // replace the file at once so a crash can not leave it half written
+    let tmp = path.with_extension("tmp");
+
+    fs::write(&tmp, content)
+        .and_then(|_| fs::rename(&tmp, path))
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+
+    Ok(true)
+}
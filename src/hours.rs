@@ -0,0 +1,195 @@
+use chrono::{Datelike, NaiveDateTime, NaiveTime, Weekday};
+use serde::Deserialize;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+/// Time of day range `HH:MM-HH:MM`, wrapping past midnight when it ends
+/// before it starts.
+#[derive(Clone, Copy, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Window {
+    start: NaiveTime,
+    end: NaiveTime,
+}
+
+impl TryFrom<String> for Window {
+    type Error = String;
+
+    fn try_from(input: String) -> Result<Self, Self::Error> {
+        let parse = |time: &str| NaiveTime::parse_from_str(time.trim(), "%H:%M").ok();
+
+        match input.split_once('-').map(|(s, e)| (parse(s), parse(e))) {
+            Some((Some(start), Some(end))) => Ok(Self { start, end }),
+            _ => Err(format!("Invalid window '{}': expected HH:MM-HH:MM", input)),
+        }
+    }
+}
+
+impl Display for Window {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        write!(
+            f,
+            "{}-{}",
+            self.start.format("%H:%M"),
+            self.end.format("%H:%M")
+        )
+    }
+}
+
+impl Window {
+    fn contains(&self, time: NaiveTime) -> bool {
+        match self.start <= self.end {
+            true => time >= self.start && time < self.end,
+            false => time >= self.start || time < self.end,
+        }
+    }
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct Day {
+    /// windows sessions may start in, an empty list makes it a day off
+    work: Option<Vec<Window>>,
+    /// windows sessions may never start in
+    blackouts: Option<Vec<Window>>,
+}
+
+/// Working hours and blackout windows, `[hours.<weekday>]` tables override
+/// the top level ones for that day. Without any, sessions start at any time.
+#[derive(Default, Deserialize)]
+#[serde(default)]
+pub struct Hours {
+    #[serde(flatten)]
+    every_day: Day,
+    mon: Day,
+    tue: Day,
+    wed: Day,
+    thu: Day,
+    fri: Day,
+    sat: Day,
+    sun: Day,
+}
+
+impl Hours {
+    fn day(&self, weekday: Weekday) -> &Day {
+        match weekday {
+            Weekday::Mon => &self.mon,
+            Weekday::Tue => &self.tue,
+            Weekday::Wed => &self.wed,
+            Weekday::Thu => &self.thu,
+            Weekday::Fri => &self.fri,
+            Weekday::Sat => &self.sat,
+            Weekday::Sun => &self.sun,
+        }
+    }
+
+    /// Says why a session may not start at `time`, if it may not.
+    pub fn check(&self, time: NaiveDateTime) -> Result<(), String> {
+        let day = self.day(time.weekday());
+        let work = day.work.as_ref().or(self.every_day.work.as_ref());
+        let blackouts = day.blackouts.as_ref().or(self.every_day.blackouts.as_ref());
+
+        if let Some(blackout) = blackouts
+            .into_iter()
+            .flatten()
+            .find(|window| window.contains(time.time()))
+        {
+            return Err(format!("Do not disturb {}.", blackout));
+        }
+
+        match work {
+            Some(work) if !work.iter().any(|window| window.contains(time.time())) => {
+                Err("Outside working hours.".to_owned())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(input: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(input, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn check(hours: &str, time: &str) -> Result<(), String> {
+        toml::from_str::<Hours>(hours).unwrap().check(at(time))
+    }
+
+    #[test]
+    fn checks_day_boundaries() {
+        let hours = r#"
+            work = ["09:00-17:00"]
+            blackouts = ["22:00-06:00"]
+
+            [sat]
+            work = []
+
+            [sun]
+            work = ["20:00-00:00"]
+            blackouts = []
+        "#;
+
+        // 2026-10-19 is a monday
+        let cases = [
+            ("2026-10-19 08:59", Err("Outside working hours.")),
+            ("2026-10-19 09:00", Ok(())),
+            ("2026-10-19 16:59", Ok(())),
+            ("2026-10-19 17:00", Err("Outside working hours.")),
+            ("2026-10-19 23:30", Err("Do not disturb 22:00-06:00.")),
+            ("2026-10-20 00:00", Err("Do not disturb 22:00-06:00.")),
+            ("2026-10-20 05:59", Err("Do not disturb 22:00-06:00.")),
+            ("2026-10-20 06:00", Err("Outside working hours.")),
+            ("2026-10-24 12:00", Err("Outside working hours.")),
+            ("2026-10-25 21:00", Ok(())),
+            ("2026-10-25 23:59", Ok(())),
+            ("2026-10-26 00:00", Err("Do not disturb 22:00-06:00.")),
+        ];
+
+        for (time, expected) in cases {
+            let expected = expected.map_err(|e| e.to_owned());
+            assert_eq!(check(hours, time), expected, "{}", time);
+        }
+    }
+
+    #[test]
+    fn checks_overlapping_blackouts() {
+        let hours = r#"
+            blackouts = ["12:00-13:00", "12:30-14:00", "23:00-01:00", "00:30-02:00"]
+        "#;
+
+        let cases = [
+            ("2026-10-19 11:59", Ok(())),
+            ("2026-10-19 12:00", Err("Do not disturb 12:00-13:00.")),
+            ("2026-10-19 12:45", Err("Do not disturb 12:00-13:00.")),
+            ("2026-10-19 13:00", Err("Do not disturb 12:30-14:00.")),
+            ("2026-10-19 14:00", Ok(())),
+            ("2026-10-19 23:00", Err("Do not disturb 23:00-01:00.")),
+            ("2026-10-20 00:45", Err("Do not disturb 23:00-01:00.")),
+            ("2026-10-20 01:30", Err("Do not disturb 00:30-02:00.")),
+            ("2026-10-20 02:00", Ok(())),
+        ];
+
+        for (time, expected) in cases {
+            let expected = expected.map_err(|e| e.to_owned());
+            assert_eq!(check(hours, time), expected, "{}", time);
+        }
+    }
+
+    #[test]
+    fn day_overrides_every_day() {
+        let hours = r#"
+            work = ["09:00-17:00"]
+            blackouts = ["12:00-13:00"]
+
+            [wed]
+            blackouts = []
+        "#;
+
+        assert!(check(hours, "2026-10-20 12:30").is_err());
+        assert!(check(hours, "2026-10-21 12:30").is_ok());
+        assert!(check(hours, "2026-10-21 18:00").is_err());
+        assert!(check("", "2026-10-24 03:00").is_ok());
+    }
+}
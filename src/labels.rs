@@ -0,0 +1,76 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use crate::header::escape;
+
+/// Task name and free form tags a session is for.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Labels {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub task: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+impl Display for Labels {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match (&self.task, self.tags.is_empty()) {
+            (Some(task), true) => write!(f, "{}", task),
+            (Some(task), false) => write!(f, "{} [{}]", task, self.tags.join(", ")),
+            (None, false) => write!(f, "[{}]", self.tags.join(", ")),
+            (None, true) => Ok(()),
+        }
+    }
+}
+
+impl Labels {
+    /// Labels from a task and a comma separated list of tags.
+    pub fn new(task: Option<&str>, tags: Option<&str>) -> Self {
+        Self {
+            task: task.filter(|t| !t.is_empty()).map(|t| t.to_owned()),
+            tags: tags
+                .map(|tags| {
+                    tags.split(',')
+                        .map(|tag| tag.trim().to_owned())
+                        .filter(|tag| !tag.is_empty())
+                        .collect()
+                })
+                .unwrap_or_default(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.task.is_none() && self.tags.is_empty()
+    }
+
+    /// `task` and `tags` request headers.
+    pub fn headers(&self) -> String {
+        let mut headers = String::new();
+
+        if let Some(task) = &self.task {
+            headers.push_str(format!("\ntask: {}", escape(task)).as_str());
+        }
+
+        if !self.tags.is_empty() {
+            headers.push_str(format!("\ntags: {}", escape(&self.tags.join(","))).as_str());
+        }
+
+        headers
+    }
+
+    /// `PDORO_TASK` and `PDORO_TAGS` for hooks.
+    pub fn env(&self) -> Vec<(&'static str, String)> {
+        let mut env = vec![];
+
+        if let Some(task) = &self.task {
+            env.push(("PDORO_TASK", task.clone()));
+        }
+
+        if !self.tags.is_empty() {
+            env.push(("PDORO_TAGS", self.tags.join(",")));
+        }
+
+        env
+    }
+}
@@ -1,24 +1,167 @@
 mod args;
 mod client;
+mod config;
+mod cron;
+mod goals;
+mod header;
+mod history;
+mod hours;
+mod labels;
+mod phase;
 mod server;
 mod time;
 mod utils;
 
-use args::Args;
+use args::{Args, Command, QueueCommand, ReportCommand, ScheduleCommand};
 use clap::Parser;
+use labels::Labels;
+use phase::Phase;
 
-use client::actions;
+use client::actions::{self, StartOptions};
+use client::{export, import, reports, status, tui, wait, watch};
+use history::Source;
 use utils::stderr;
 
 fn main() {
     let args = Args::parse();
 
+    if let Some(command) = args.command {
+        return match command {
+            Command::Start {
+                time,
+                until,
+                roll_over,
+                profile,
+                is_break,
+                overtime,
+                remind_every,
+                max_reminders,
+                warnings,
+                force,
+                task,
+                tags,
+                callback_with_args,
+            } => {
+                let options = StartOptions {
+                    profile,
+                    phase: match is_break {
+                        true => Phase::Break,
+                        false => Phase::Work,
+                    },
+                    overtime,
+                    remind_every,
+                    max_reminders,
+                    warnings,
+                    force,
+                    labels: Labels { task, tags },
+                    callback_with_args,
+                };
+
+                match until {
+                    Some(end_time) => actions::start_until(&end_time, roll_over, &options),
+                    None => actions::start(&time.unwrap_or_default(), &options),
+                }
+            }
+            Command::Stopwatch {
+                every,
+                force,
+                task,
+                tags,
+                callback_with_args,
+            } => actions::start_stopwatch(
+                every.as_deref(),
+                force,
+                &Labels { task, tags },
+                callback_with_args.as_deref(),
+            ),
+            Command::Pause {
+                resume_after,
+                halt_after,
+            } => actions::pause_counter(resume_after.as_deref(), halt_after.as_deref()),
+            Command::Resume => actions::resume_counter(),
+            Command::Ack => actions::acknowledge(),
+            Command::Extend { time } => actions::extend(&time),
+            Command::Watch { extend, template } => watch::watch(&extend, template.as_deref()),
+            Command::Tui { callback_with_args } => tui::tui(callback_with_args.as_deref()),
+            Command::Interrupt {
+                note,
+                external: _,
+                internal,
+                pause,
+            } => actions::interrupt(
+                match internal {
+                    true => Source::Internal,
+                    false => Source::External,
+                },
+                note.as_deref(),
+                pause,
+            ),
+            Command::Note { text, prompt, edit } => match (text, prompt, edit) {
+                (Some(text), _, _) => actions::note(&text),
+                (None, true, _) => actions::prompt_note(),
+                (None, false, _) => actions::edit_note(),
+            },
+            Command::Goal => actions::goal(),
+            Command::Status { format, template } => status::status(format, template.as_deref()),
+            Command::Prompt { template } => status::prompt(template.as_deref()),
+            Command::Wait { timeout, until } => wait::wait(timeout.as_deref(), until),
+            Command::Report { command } => match command {
+                ReportCommand::Sessions { date } => reports::sessions(date.as_deref()),
+                ReportCommand::Interruptions { date } => reports::interruptions(date.as_deref()),
+                ReportCommand::Timesheet {
+                    week: _,
+                    from,
+                    to,
+                    by,
+                    round,
+                    format,
+                } => reports::timesheet(
+                    from.as_deref().zip(to.as_deref()),
+                    by,
+                    round.as_deref(),
+                    format,
+                ),
+            },
+            Command::Export { format, from, to } => {
+                export::export(format, from.as_deref(), to.as_deref())
+            }
+            Command::Import {
+                path,
+                format,
+                columns,
+                dry_run,
+            } => import::import(&path, format, &columns, dry_run),
+            Command::Queue { command } => match command {
+                QueueCommand::Add {
+                    time,
+                    task,
+                    tags,
+                    profile,
+                    callback_with_args,
+                } => actions::queue_add(
+                    &time,
+                    &Labels { task, tags },
+                    profile.as_deref(),
+                    callback_with_args.as_deref(),
+                ),
+                QueueCommand::List => actions::queue_list(),
+                QueueCommand::Remove { position } => actions::queue_remove(position),
+                QueueCommand::Move { from, to } => actions::queue_move(from, to),
+                QueueCommand::Clear => actions::queue_clear(),
+                QueueCommand::Next { force } => actions::queue_next(force),
+            },
+            Command::Schedule { command } => match command {
+                ScheduleCommand::List => actions::schedule_list(),
+            },
+        };
+    }
+
     if args.remaining {
         return actions::remaining();
     }
 
     if args.is_counter_running {
-        return actions::is_counter_running();
+        return actions::is_counter_running(args.awaiting_ack);
     }
 
     if let Some(input) = args.is_valid_time {
@@ -27,7 +170,19 @@ fn main() {
 
     match (args.time, args.callback_with_args) {
         (Some(time), Some(callback_with_args)) => {
-            return actions::start(&time, &callback_with_args)
+            let options = StartOptions {
+                profile: None,
+                phase: Phase::Work,
+                overtime: false,
+                remind_every: None,
+                max_reminders: None,
+                warnings: vec![],
+                force: false,
+                labels: Labels::default(),
+                callback_with_args: Some(callback_with_args),
+            };
+
+            return actions::start(&time, &options);
         }
         (Some(_), None) | (None, Some(_)) => {
             return stderr("Both time and callback_with_args must be provided.")
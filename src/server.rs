@@ -1,9 +1,13 @@
 use request::Request;
 use response::Response;
 use status_code::StatusCode;
-use std::io::Read;
+use std::io::{Read, Result as IoResult};
 use std::net::TcpListener;
 
+/// Requests longer than this are refused, so a client can not make the
+/// server buffer without end.
+pub const MAX_REQUEST: usize = 64 * 1024;
+
 pub trait Handler {
     fn handle_request(&self, request: &Request) -> Response;
 
@@ -30,31 +34,63 @@ impl Server {
 
         loop {
             match listener.accept() {
-                Ok((mut stream, _)) => {
-                    let mut buffer = [0 as u8; 256];
-                    match stream.read(&mut buffer) {
-                        Ok(_) => {
-                            let response = match Request::try_from(&buffer[..]) {
+                Ok((mut stream, _)) => match read_request(&mut stream) {
+                    Ok(buffer) => {
+                        let response = match buffer.len() > MAX_REQUEST {
+                            true => handler.handle_bad_request(
+                                format!("Request too large, the limit is {} bytes", MAX_REQUEST)
+                                    .as_str(),
+                            ),
+                            false => match Request::try_from(&buffer[..]) {
                                 Ok(request) => handler.handle_request(&request),
                                 Err(e) => handler.handle_bad_request(&e),
-                            };
+                            },
+                        };
 
-                            if let Err(e) = response.send(&mut stream) {
-                                println!("Failed to send reponse: {}", e)
-                            }
+                        if let Err(e) = response.send(&mut stream) {
+                            println!("Failed to send reponse: {}", e)
                         }
-                        Err(e) => println!("Failed to read from connection: {}", e),
                     }
-                }
+                    Err(e) => println!("Failed to read from connection: {}", e),
+                },
                 Err(e) => println!("Failed to establish a connection: {}", e),
             }
         }
     }
 }
 
+/// Reads until the request ends with `;` or the client closes its side.
+/// Only the first `MAX_REQUEST` bytes and one more are kept, the rest of a
+/// longer request is read and dropped so the client still gets the answer.
+fn read_request(stream: &mut impl Read) -> IoResult<Vec<u8>> {
+    let mut request = vec![];
+    let mut buffer = [0_u8; 1024];
+
+    loop {
+        let n = match stream.read(&mut buffer)? {
+            0 => break,
+            n => n,
+        };
+
+        let kept = (MAX_REQUEST + 1).saturating_sub(request.len()).min(n);
+        request.extend_from_slice(&buffer[..kept]);
+
+        if buffer[n - 1] == b';' {
+            break;
+        }
+    }
+
+    Ok(request)
+}
+
 pub mod controllers;
+pub mod hooks;
+pub mod progress;
+pub mod queue;
 pub mod request;
 pub mod response;
 pub mod router;
+pub mod scheduler;
 pub mod status_code;
+pub mod status_file;
 pub mod tcp_handler;
@@ -1,11 +1,23 @@
+use crate::config::{Config, Profile};
+use crate::header::{escape, unescape};
+use crate::history::{self, Interruption, Kind, Outcome, Record, Source};
+use crate::labels::Labels;
+use crate::phase::Phase;
+use crate::time::{Bounds, Time};
 use crate::utils::sleep;
 
+use super::hooks::run_hook;
+use super::progress;
+use super::queue::{self, Entry};
 use super::request::Request;
 use super::response::Response;
 use super::status_code::StatusCode;
+use super::status_file;
 
+use chrono::{DateTime, Local};
 use std::process::Command;
-use std::sync::RwLock;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{RwLock, RwLockWriteGuard};
 use std::thread;
 
 enum CounterState {
@@ -13,10 +25,98 @@ enum CounterState {
     Running,
     Halting,
     Paused,
+    Overtime,
+    AwaitingAck,
+}
+
+struct Session {
+    /// lets a counter thread notice its session was ended elsewhere
+    id: u32,
+    kind: Kind,
+    started_at: DateTime<Local>,
+    duration: u32,
+    elapsed: u32,
+    paused: u32,
+    overtime: u32,
+    reminders: u32,
+    labels: Labels,
+    interruptions: Vec<Interruption>,
+    notes: Vec<String>,
+    options: SessionOptions,
+}
+
+/// Pomodoro settings taken from start request headers or a config profile.
+#[derive(Default)]
+struct SessionOptions {
+    phase: Phase,
+    allow_overtime: bool,
+    remind_every: Option<u32>,
+    max_reminders: Option<u32>,
+    /// seconds before the end at which to warn
+    warnings: Vec<u32>,
+    warning_callback: Option<String>,
+    /// accepted lengths for the phase, from the profile
+    bounds: Bounds,
+}
+
+impl SessionOptions {
+    fn from_headers(request: &Request, profile: &Profile) -> Result<Self, ()> {
+        let phase = match request.header("phase") {
+            Some("break") => Phase::Break,
+            _ => Phase::Work,
+        };
+
+        let warnings = match request.header("warnings") {
+            Some(h) => h
+                .split(',')
+                .map(|w| w.parse::<u32>().map_err(|_| ()))
+                .collect::<Result<_, _>>()?,
+            None => vec![],
+        };
+
+        Ok(Self {
+            phase,
+            allow_overtime: request.header("overtime") == Some("true"),
+            remind_every: parse_header_number(request, "remind-every")?.filter(|e| *e > 0),
+            max_reminders: parse_header_number(request, "max-reminders")?,
+            warnings,
+            warning_callback: request.header("warning-callback").map(|h| h.to_owned()),
+            bounds: *profile.bounds(phase),
+        })
+    }
+}
+
+impl From<&Profile> for SessionOptions {
+    fn from(profile: &Profile) -> Self {
+        Self {
+            phase: Phase::Work,
+            allow_overtime: profile.overtime,
+            remind_every: profile.remind_every,
+            max_reminders: profile.max_reminders,
+            warnings: profile.warnings.clone(),
+            warning_callback: profile.warning_callback.clone(),
+            bounds: *profile.bounds(Phase::Work),
+        }
+    }
+}
+
+struct PauseLimits {
+    resume_after: Option<u32>,
+    halt_after: Option<u32>,
 }
 
 static REMAINING_TIME: RwLock<u32> = RwLock::new(0);
 static COUNTER_STATE: RwLock<CounterState> = RwLock::new(CounterState::Pristine);
+static SESSION: RwLock<Option<Session>> = RwLock::new(None);
+static NEXT_SESSION_ID: AtomicU32 = AtomicU32::new(0);
+/// id and outcome of the session that ended last, for `pdoro wait`, set
+/// under the `COUNTER_STATE` lock that makes the counter idle
+static LAST_ENDED: RwLock<Option<(u32, Outcome)>> = RwLock::new(None);
+static WAITING_FOR_HOURS: AtomicBool = AtomicBool::new(false);
+static PAUSE_LIMITS: RwLock<PauseLimits> = RwLock::new(PauseLimits {
+    resume_after: None,
+    halt_after: None,
+});
 
 pub fn health_check() -> Response {
     Response::new(StatusCode::Ok, Some("I'm alive".to_owned()))
@@ -27,32 +127,216 @@ pub fn not_found() -> Response {
 }
 
 pub fn start_pomodoro(request: &Request) -> Response {
-    match COUNTER_STATE.try_read() {
-        Ok(cs) => match *cs {
-            CounterState::Pristine => {}
-            _ => {
-                return Response::new(
-                    StatusCode::Conflict,
-                    Some("Pomodoro already running.".to_owned()),
-                )
-            }
-        },
-        Err(_) => {
+    if let Err(res) = check_hours(request) {
+        return res;
+    }
+
+    let claim = match claim_counter() {
+        Ok(claim) => claim,
+        Err(res) => return res,
+    };
+
+    let arg1 = match request.arg1() {
+        Some(arg1) => arg1,
+        None => return Response::new(StatusCode::BadRequest, Some("Missing args.".to_owned())),
+    };
+
+    let seconds = match arg1.parse::<u32>() {
+        Ok(s) if s > 0 => s,
+        _ => {
             return Response::new(
-                StatusCode::InternalServerError,
-                Some("Failed to read counter state.".to_owned()),
+                StatusCode::BadRequest,
+                Some("Invalid time format.".to_owned()),
             )
         }
-    }
+    };
 
-    let (arg1, arg2) = match (request.arg1(), request.arg2()) {
-        (Some(a), Some(b)) => (a, b),
-        _ => return Response::new(StatusCode::BadRequest, Some("Missing args.".to_owned())),
+    let profile = match Config::global().profile(request.header("profile")) {
+        Ok(profile) => profile,
+        Err(e) => return Response::new(StatusCode::BadRequest, Some(format!("{}.", e))),
     };
 
-    let seconds = match arg1.parse::<u32>() {
-        Ok(s) => s,
+    let options = match SessionOptions::from_headers(request, &profile) {
+        Ok(options) => options,
         Err(_) => {
+            return Response::new(StatusCode::BadRequest, Some("Invalid headers.".to_owned()))
+        }
+    };
+
+    if let Err(e) = options.bounds.check(seconds) {
+        return Response::new(StatusCode::BadRequest, Some(e.to_string()));
+    }
+
+    let labels = Labels::new(request.header("task"), request.header("tags"));
+
+    // the callback is optional, and escaped like header values
+    let callback_with_args = request.arg2().filter(|a| !a.is_empty()).map(unescape);
+
+    let id = start_session(claim, Kind::Pomodoro, seconds, labels, options);
+    spawn_counter(id, callback_with_args, None);
+
+    Response::new(StatusCode::Created, Some("Pomodoro started.".to_owned()))
+}
+
+pub fn start_next_queued(request: &Request) -> Response {
+    if let Err(res) = check_hours(request) {
+        return res;
+    }
+
+    next_queued()
+}
+
+/// Starts the first queued pomodoro.
+fn next_queued() -> Response {
+    let claim = match claim_counter() {
+        Ok(claim) => claim,
+        Err(res) => return res,
+    };
+
+    match queue::pop() {
+        Some(entry) => start_entry(claim, entry),
+        None => Response::new(StatusCode::Conflict, Some("Queue is empty.".to_owned())),
+    }
+}
+
+/// Starts a scheduled pomodoro, or puts it first in the queue when another
+/// session is running or working hours do not allow it yet. Runs of a
+/// schedule that already waits in the queue are merged into that one.
+pub fn start_scheduled(name: &str, entry: Entry) {
+    let mut env = entry.labels.env();
+    env.push(("PDORO_SCHEDULE", name.to_owned()));
+
+    if Config::global()
+        .hours()
+        .check(Local::now().naive_local())
+        .is_err()
+    {
+        defer_queue();
+
+        return match queue::push_front(entry) {
+            true => run_hook("schedule-deferred", &env),
+            false => println!("Schedule {} already waits in the queue", name),
+        };
+    }
+
+    match claim_counter() {
+        Ok(claim) => {
+            start_entry(claim, entry);
+            run_hook("scheduled", &env);
+        }
+        Err(_) => match queue::push_front(entry) {
+            true => run_hook("schedule-queued", &env),
+            false => println!("Schedule {} already waits in the queue", name),
+        },
+    }
+}
+
+/// Starts `entry` with the settings of its profile.
+fn start_entry(claim: Claim, entry: Entry) -> Response {
+    let options = match Config::global().profile(entry.profile.as_deref()) {
+        Ok(profile) => SessionOptions::from(&profile),
+        Err(e) => {
+            println!("{}, starting queued pomodoro with defaults", e);
+            SessionOptions::default()
+        }
+    };
+
+    let msg = format!("Pomodoro started: {}", entry);
+
+    let id = start_session(claim, Kind::Pomodoro, entry.seconds, entry.labels, options);
+    spawn_counter(id, entry.callback, None);
+
+    Response::new(StatusCode::Created, Some(msg))
+}
+
+/// Moves on to the next queued pomodoro after one completes, either right
+/// away or once confirmed with `queue-next`, as configured. Schedule runs
+/// always start by themselves.
+fn advance_queue() {
+    if queue::is_empty() {
+        return;
+    }
+
+    if !Config::global().queue_auto_start() && !queue::first_is_scheduled() {
+        return run_hook("queue-waiting", &[]);
+    }
+
+    if Config::global()
+        .hours()
+        .check(Local::now().naive_local())
+        .is_err()
+    {
+        defer_queue();
+        return run_hook("queue-deferred", &[]);
+    }
+
+    let res = next_queued();
+
+    match res.status_code() {
+        StatusCode::Created => run_hook("queue-started", &session_env()),
+        _ => println!(
+            "Failed to start queued pomodoro: {}",
+            res.body().unwrap_or_default()
+        ),
+    }
+}
+
+/// Advances the queue once working hours allow starting again, unless a
+/// session was started by hand in the meantime.
+fn defer_queue() {
+    if WAITING_FOR_HOURS.swap(true, Ordering::Relaxed) {
+        return;
+    }
+
+    thread::spawn(|| {
+        while Config::global()
+            .hours()
+            .check(Local::now().naive_local())
+            .is_err()
+        {
+            sleep(1);
+        }
+
+        WAITING_FOR_HOURS.store(false, Ordering::Relaxed);
+
+        let pristine = matches!(*COUNTER_STATE.read().unwrap(), CounterState::Pristine);
+
+        if pristine {
+            advance_queue();
+        }
+    });
+}
+
+/// Refuses starts outside working hours or in a do-not-disturb window,
+/// unless the request is forced.
+fn check_hours(request: &Request) -> Result<(), Response> {
+    if request.header("force") == Some("true") {
+        return Ok(());
+    }
+
+    match Config::global().hours().check(Local::now().naive_local()) {
+        Ok(_) => Ok(()),
+        Err(reason) => Err(Response::new(
+            StatusCode::Conflict,
+            Some(format!("{} Use --force to start anyway.", reason)),
+        )),
+    }
+}
+
+pub fn start_stopwatch(request: &Request) -> Response {
+    if let Err(res) = check_hours(request) {
+        return res;
+    }
+
+    let claim = match claim_counter() {
+        Ok(claim) => claim,
+        Err(res) => return res,
+    };
+
+    let milestone = match request.arg1().map(|a| a.parse::<u32>()) {
+        None | Some(Ok(0)) => None,
+        Some(Ok(seconds)) => Some(seconds),
+        Some(Err(_)) => {
             return Response::new(
                 StatusCode::BadRequest,
                 Some("Invalid time format.".to_owned()),
@@ -60,78 +344,357 @@ pub fn start_pomodoro(request: &Request) -> Response {
         }
     };
 
-    let callback_with_args = arg2.to_owned();
+    let labels = Labels::new(request.header("task"), request.header("tags"));
 
-    {
-        let mut rt = REMAINING_TIME.write().unwrap();
-        *rt = seconds;
+    let id = start_session(claim, Kind::Stopwatch, 0, labels, SessionOptions::default());
+    spawn_counter(id, request.arg2().map(unescape), milestone);
 
-        let mut cs = COUNTER_STATE.write().unwrap();
-        *cs = CounterState::Running;
+    Response::new(StatusCode::Created, Some("Stopwatch started.".to_owned()))
+}
+
+fn parse_header_number(request: &Request, name: &str) -> Result<Option<u32>, ()> {
+    match request.header(name).map(|h| h.parse::<u32>()) {
+        None => Ok(None),
+        Some(Ok(number)) => Ok(Some(number)),
+        Some(Err(_)) => Err(()),
     }
+}
 
-    thread::spawn(move || {
-        // prevent index from going below 0
-        let mut i = match seconds {
-            1 => 1,
-            _ => seconds - 1,
-        };
+/// Counter locks held from checking that no session is running until the
+/// next one is started, so two concurrent starts cannot both succeed.
+struct Claim {
+    rt: RwLockWriteGuard<'static, u32>,
+    cs: RwLockWriteGuard<'static, CounterState>,
+}
+
+/// A finished session waiting in overtime or for acknowledgement is over
+/// as soon as the next one starts.
+fn claim_counter() -> Result<Claim, Response> {
+    let rt = REMAINING_TIME.write().unwrap();
+    let mut cs = COUNTER_STATE.write().unwrap();
+
+    match *cs {
+        CounterState::Pristine => {}
+        CounterState::Overtime | CounterState::AwaitingAck => end_finished(&mut cs),
+        _ => {
+            return Err(Response::new(
+                StatusCode::Conflict,
+                Some("Pomodoro already running.".to_owned()),
+            ))
+        }
+    }
+
+    Ok(Claim { rt, cs })
+}
 
-        let mut stored_state = CounterState::Running;
+fn start_session(
+    claim: Claim,
+    kind: Kind,
+    seconds: u32,
+    labels: Labels,
+    options: SessionOptions,
+) -> u32 {
+    let id = NEXT_SESSION_ID.fetch_add(1, Ordering::Relaxed);
+
+    let Claim { mut rt, mut cs } = claim;
+    *rt = seconds;
+    *cs = CounterState::Running;
+
+    let mut session = SESSION.write().unwrap();
+    *session = Some(Session {
+        id,
+        kind,
+        started_at: Local::now(),
+        duration: seconds,
+        elapsed: 0,
+        paused: 0,
+        overtime: 0,
+        reminders: 0,
+        labels,
+        interruptions: vec![],
+        notes: vec![],
+        options,
+    });
 
-        loop {
+    id
+}
+
+/// Ticks session `id` every second until it completes, is halted or is
+/// ended elsewhere. Pomodoros count `REMAINING_TIME` down, warning when it
+/// reaches one of `warnings`, then up again while in overtime, and rerun the
+/// callback every `remind_every` seconds until acknowledged; stopwatches count
+/// it up and run the callback every `milestone` seconds.
+///
+/// Paused time is never counted, so warnings shift with pauses.
+fn spawn_counter(id: u32, callback_with_args: Option<String>, milestone: Option<u32>) {
+    thread::spawn(move || {
+        let env = session_env();
+        let mut paused_for = 0;
+        let mut finished_for: u32 = 0;
+
+        let outcome = loop {
             sleep(1);
-            i -= 1;
+
+            let mut stored_outcome = None;
+            let mut auto_state = None;
+            let mut milestone_reached = false;
+            let mut finished_event = None;
+            let mut reminder_due = false;
+            let mut warning_due = None;
 
             // lock 1
             {
                 let mut rt = REMAINING_TIME.write().unwrap();
-                let cs = COUNTER_STATE.read().unwrap(); // read for better performance
+                let mut cs = COUNTER_STATE.write().unwrap();
+                let mut session = SESSION.write().unwrap();
+
+                let session = match session.as_mut() {
+                    Some(session) if session.id == id => session,
+                    _ => break None,
+                };
 
                 match *cs {
                     CounterState::Paused => {
-                        i += 1;
-                        continue;
+                        paused_for += 1;
+                        session.paused += 1;
+                        auto_state = pause_limit_reached(paused_for);
                     }
                     CounterState::Halting => {
                         *rt = 0;
+                        stored_outcome = Some(Outcome::Halted);
+                    }
+                    CounterState::Overtime | CounterState::AwaitingAck => {
+                        finished_for += 1;
+
+                        if let CounterState::Overtime = *cs {
+                            session.overtime += 1;
+                            *rt = session.overtime;
+                        }
+
+                        reminder_due = session
+                            .options
+                            .remind_every
+                            .is_some_and(|every| finished_for.is_multiple_of(every))
+                            && session
+                                .options
+                                .max_reminders
+                                .is_none_or(|max| session.reminders < max);
+
+                        if reminder_due {
+                            session.reminders += 1;
+                        }
                     }
                     _ => {
-                        *rt = i;
+                        paused_for = 0;
+                        // counts again from the end of a pomodoro extended while finished
+                        finished_for = 0;
+                        session.elapsed += 1;
+
+                        match session.kind {
+                            Kind::Pomodoro => {
+                                *rt = rt.saturating_sub(1);
+
+                                if session.options.warnings.contains(&rt) {
+                                    warning_due =
+                                        Some((*rt, session.options.warning_callback.clone()));
+                                }
+
+                                // the state changes under this lock, so an extend can
+                                // not slip in between
+                                if *rt == 0 {
+                                    match (
+                                        session.options.allow_overtime,
+                                        session.options.remind_every,
+                                    ) {
+                                        (true, _) => {
+                                            *cs = CounterState::Overtime;
+                                            finished_event = Some("overtime");
+                                        }
+                                        (false, Some(_)) => {
+                                            *cs = CounterState::AwaitingAck;
+                                            finished_event = Some("awaiting-ack");
+                                        }
+                                        (false, None) => stored_outcome = Some(Outcome::Completed),
+                                    }
+                                }
+                            }
+                            Kind::Stopwatch => {
+                                *rt += 1;
+                                milestone_reached = milestone.is_some_and(|m| rt.is_multiple_of(m));
+                            }
+                        }
                     }
                 }
+            } // unlock lock 1
+
+            if let Some(state) = auto_state {
+                apply_auto_state(state, &env);
+            }
+
+            if milestone_reached {
+                if let Some(callback_with_args) = &callback_with_args {
+                    run_callback(callback_with_args);
+                }
+                run_hook("milestone", &env);
+            }
 
-                if *rt <= 0 {
-                    stored_state = CounterState::Pristine;
+            if let Some((left, warning_callback)) = warning_due {
+                if let Some(warning_callback) = &warning_callback {
+                    run_callback(warning_callback);
                 }
-            } // unlock lock 1
+                let mut env = env.clone();
+                env.push(("PDORO_REMAINING", left.to_string()));
+                run_hook("warning", &env);
+            }
 
-            match stored_state {
-                // match all states asigned to `stored_state` during lock 1
-                CounterState::Pristine => {
-                    {
-                        let mut cs = COUNTER_STATE.write().unwrap();
-                        *cs = CounterState::Pristine;
-                    }
-                    break;
+            if let Some(event) = finished_event {
+                if let Some(callback_with_args) = &callback_with_args {
+                    run_callback(callback_with_args);
                 }
-                _ => {}
+                run_hook(event, &env);
+            }
+
+            if reminder_due {
+                if let Some(callback_with_args) = &callback_with_args {
+                    run_callback(callback_with_args);
+                }
+                run_hook("reminder", &env);
+            }
+
+            // match all outcomes asigned to `stored_outcome` during lock 1
+            if let Some(outcome) = stored_outcome {
+                break Some(outcome);
             }
+
+            // also while paused, so `pdoro prompt` can tell a stopped server by its age
+            status_file::update();
+        };
+
+        let session = match outcome {
+            Some(outcome) => end_session(id, outcome),
+            None => None,
+        };
+
+        if let (Some(Kind::Pomodoro), Some(callback_with_args)) = (session, &callback_with_args) {
+            run_callback(callback_with_args);
         }
 
-        run_callback(&callback_with_args);
+        if let (Some(Kind::Pomodoro), Some(Outcome::Completed)) = (session, outcome) {
+            advance_queue();
+        }
+
+        status_file::update();
     });
+}
+
+/// Records session `id` and resets the counter, unless it already ended.
+fn end_session(id: u32, outcome: Outcome) -> Option<Kind> {
+    let session = {
+        let mut cs = COUNTER_STATE.write().unwrap();
+        let mut session = SESSION.write().unwrap();
+
+        match session.as_ref() {
+            Some(session) if session.id == id => *cs = CounterState::Pristine,
+            _ => return None,
+        }
+
+        // under the same locks, so the counter is never seen idle without it
+        *LAST_ENDED.write().unwrap() = Some((id, outcome));
+
+        session.take()?
+    };
+
+    record_session(&session, outcome);
+    Some(session.kind)
+}
+
+/// Ends the session currently in overtime or awaiting acknowledgement, its
+/// counter thread stops on its own.
+fn end_finished(cs: &mut CounterState) {
+    *cs = CounterState::Pristine;
+
+    let session = SESSION.write().unwrap().take();
+
+    if let Some(session) = session {
+        *LAST_ENDED.write().unwrap() = Some((session.id, Outcome::Completed));
+        record_session(&session, Outcome::Completed);
+    }
+}
+
+fn record_session(session: &Session, outcome: Outcome) {
+    let record = Record {
+        kind: session.kind,
+        phase: session.options.phase,
+        started_at: session.started_at,
+        ended_at: Local::now(),
+        duration: match session.kind {
+            Kind::Pomodoro => Some(session.duration),
+            Kind::Stopwatch => None,
+        },
+        elapsed: session.elapsed,
+        paused: session.paused,
+        overtime: session.overtime,
+        labels: session.labels.clone(),
+        interruptions: session.interruptions.clone(),
+        notes: session.notes.clone(),
+        outcome,
+    };
+
+    // counted before it is appended, so a new day reads history without it
+    progress::add(&record);
+
+    if let Err(e) = history::append(&Config::global().history_path(), &record) {
+        println!("Failed to record session: {}", e)
+    }
+}
+
+/// Task and tags of the current session, for hooks.
+fn session_env() -> Vec<(&'static str, String)> {
+    match SESSION.read().unwrap().as_ref() {
+        Some(session) => session.labels.env(),
+        None => vec![],
+    }
+}
 
-    return Response::new(StatusCode::Created, Some("Pomodoro started.".to_owned()));
+fn pause_limit_reached(paused_for: u32) -> Option<CounterState> {
+    let limits = PAUSE_LIMITS.read().unwrap();
+
+    match (limits.resume_after, limits.halt_after) {
+        (_, Some(max)) if paused_for >= max => Some(CounterState::Halting),
+        (Some(after), _) if paused_for >= after => Some(CounterState::Running),
+        _ => None,
+    }
+}
+
+fn apply_auto_state(state: CounterState, env: &[(&str, String)]) {
+    let mut cs = COUNTER_STATE.write().unwrap();
+
+    // the counter may have been resumed or halted by hand in the meantime
+    if let CounterState::Paused = *cs {
+        let event = match state {
+            CounterState::Halting => "auto-halt",
+            _ => "auto-resume",
+        };
+
+        *cs = state;
+        run_hook(event, env);
+    }
 }
 
 pub fn run_callback(callback_with_args: &str) {
     let (callback, args) = parse_callback_with_args(callback_with_args);
 
-    Command::new(callback)
-        .args(args)
-        .spawn()
-        .expect("Failed to run callback.");
+    match Command::new(callback).args(args).spawn() {
+        // reap the callback without blocking the counter
+        Ok(mut child) => {
+            thread::spawn(move || {
+                if let Err(e) = child.wait() {
+                    println!("Failed to wait for callback: {}", e)
+                }
+            });
+        }
+        Err(e) => println!("Failed to run callback: {}", e),
+    }
 }
 
 pub fn parse_callback_with_args(callback_with_args: &str) -> (String, Vec<String>) {
@@ -139,39 +702,104 @@ pub fn parse_callback_with_args(callback_with_args: &str) -> (String, Vec<String
     let callback = split.next().unwrap().to_owned();
     let args = split.map(|s| s.to_owned()).collect();
 
-    return (callback, args);
+    (callback, args)
 }
 
+/// Responds with `<seconds> <mode>`, where seconds are the time left of a
+/// pomodoro or the time elapsed of a stopwatch or overtime, followed by the
+/// session's `phase`, `elapsed`, `total`, `task`, `tags` and `interruptions`
+/// header lines, the pomodoros completed today, the daily `goal` progress and
+/// the `next` queued pomodoro.
 pub fn remaining_pomodoro() -> Response {
+    let progress = progress::today();
+    let next = queue::first();
+
     let remaining = REMAINING_TIME.read().unwrap();
     let state = COUNTER_STATE.read().unwrap();
+    let session = SESSION.read().unwrap();
+    // read with the state, which changes together with it
+    let last_ended = *LAST_ENDED.read().unwrap();
 
     let status_code = match *state {
         CounterState::Paused => StatusCode::NotModified,
         _ => StatusCode::Ok,
     };
 
-    return Response::new(status_code, Some(remaining.to_string()));
+    let mode = match (&*state, session.as_ref().map(|s| s.kind)) {
+        (CounterState::Pristine, _) | (_, None) => "idle",
+        (CounterState::Overtime, _) => "overtime",
+        (CounterState::AwaitingAck, _) => "awaiting-ack",
+        (_, Some(Kind::Pomodoro)) => "countdown",
+        (_, Some(Kind::Stopwatch)) => "stopwatch",
+    };
+
+    let mut headers = String::new();
+
+    if let (false, Some(session)) = (matches!(*state, CounterState::Pristine), session.as_ref()) {
+        headers.push_str(format!("\nsession: {}", session.id).as_str());
+        headers.push_str(format!("\nphase: {}", session.options.phase.name()).as_str());
+        headers.push_str(format!("\nelapsed: {}", session.elapsed).as_str());
+
+        if let Kind::Pomodoro = session.kind {
+            headers.push_str(format!("\ntotal: {}", session.duration).as_str());
+        }
+
+        headers.push_str(session.labels.headers().as_str());
+
+        if !session.interruptions.is_empty() {
+            headers.push_str(format!("\ninterruptions: {}", session.interruptions.len()).as_str());
+        }
+    }
+
+    headers.push_str(format!("\npomodoros: {}", progress.pomodoros).as_str());
+
+    if !progress.target.is_empty() {
+        headers.push_str(format!("\ngoal: {}", progress).as_str());
+    }
+
+    if let Some(next) = next {
+        headers.push_str(format!("\nnext: {}", escape(&next)).as_str());
+    }
+
+    if let Some((id, outcome)) = last_ended {
+        let outcome = match outcome {
+            Outcome::Completed => "completed",
+            Outcome::Halted => "halted",
+        };
+
+        headers.push_str(format!("\nended: {} {}", id, outcome).as_str());
+    }
+
+    Response::new(
+        status_code,
+        Some(format!("{} {}{}", remaining, mode, headers)),
+    )
 }
 
 pub fn halt_counter() -> Response {
     let mut cs = COUNTER_STATE.write().unwrap();
     match *cs {
-        CounterState::Halting => {
-            return Response::new(
-                StatusCode::Conflict,
-                Some("Pomodoro counter already halting...".to_owned()),
-            )
-        }
+        CounterState::Halting => Response::new(
+            StatusCode::Conflict,
+            Some("Pomodoro counter already halting...".to_owned()),
+        ),
         CounterState::Pristine => {
-            return Response::new(StatusCode::Conflict, Some("Nothing to halt.".to_owned()))
+            Response::new(StatusCode::Conflict, Some("Nothing to halt.".to_owned()))
+        }
+        CounterState::Overtime => {
+            end_finished(&mut cs);
+            Response::new(StatusCode::Ok, Some("Overtime ended.".to_owned()))
+        }
+        CounterState::AwaitingAck => {
+            end_finished(&mut cs);
+            Response::new(StatusCode::Ok, Some("Pomodoro acknowledged.".to_owned()))
         }
         _ => {
             *cs = CounterState::Halting;
-            return Response::new(
+            Response::new(
                 StatusCode::Ok,
                 Some("Pomodoro counter halting...".to_owned()),
-            );
+            )
         }
     }
 }
@@ -180,27 +808,321 @@ pub fn pause_resume_counter() -> Response {
     let mut cs = COUNTER_STATE.write().unwrap();
     match *cs {
         CounterState::Running => {
+            set_pause_limits(None, None);
+            *cs = CounterState::Paused;
+            Response::new(StatusCode::Ok, Some("Pomodoro counter paused.".to_owned()))
+        }
+        CounterState::Paused => {
+            *cs = CounterState::Running;
+            Response::new(StatusCode::Ok, Some("Pomodoro counter resumed.".to_owned()))
+        }
+        _ => Response::new(
+            StatusCode::Conflict,
+            Some("nothing to pause/resume.".to_owned()),
+        ),
+    }
+}
+
+pub fn pause_counter(request: &Request) -> Response {
+    let (resume_after, halt_after) = match (
+        parse_pause_limit(request.arg1()),
+        parse_pause_limit(request.arg2()),
+    ) {
+        (Ok(a), Ok(b)) => (a, b),
+        _ => {
+            return Response::new(
+                StatusCode::BadRequest,
+                Some("Invalid time format.".to_owned()),
+            )
+        }
+    };
+
+    let mut cs = COUNTER_STATE.write().unwrap();
+    match *cs {
+        CounterState::Running => {
+            set_pause_limits(resume_after, halt_after);
             *cs = CounterState::Paused;
-            return Response::new(StatusCode::Ok, Some("Pomodoro counter paused.".to_owned()));
+            Response::new(StatusCode::Ok, Some("Pomodoro counter paused.".to_owned()))
         }
+        // the limits of a pause are set when it starts
+        CounterState::Paused if resume_after.is_some() || halt_after.is_some() => Response::new(
+            StatusCode::NotModified,
+            Some("Pomodoro counter already paused, --for and --max ignored.".to_owned()),
+        ),
+        CounterState::Paused => Response::new(
+            StatusCode::NotModified,
+            Some("Pomodoro counter already paused.".to_owned()),
+        ),
+        _ => Response::new(StatusCode::Conflict, Some("Nothing to pause.".to_owned())),
+    }
+}
+
+/// A missing or zero limit means the pause has no limit.
+fn parse_pause_limit(arg: Option<&str>) -> Result<Option<u32>, ()> {
+    match arg.map(|a| a.parse::<u32>()) {
+        None | Some(Ok(0)) => Ok(None),
+        Some(Ok(seconds)) => Ok(Some(seconds)),
+        Some(Err(_)) => Err(()),
+    }
+}
+
+fn set_pause_limits(resume_after: Option<u32>, halt_after: Option<u32>) {
+    let mut limits = PAUSE_LIMITS.write().unwrap();
+    limits.resume_after = resume_after;
+    limits.halt_after = halt_after;
+}
+
+pub fn resume_counter() -> Response {
+    let mut cs = COUNTER_STATE.write().unwrap();
+    match *cs {
         CounterState::Paused => {
             *cs = CounterState::Running;
-            return Response::new(StatusCode::Ok, Some("Pomodoro counter resumed.".to_owned()));
+            Response::new(StatusCode::Ok, Some("Pomodoro counter resumed.".to_owned()))
         }
+        CounterState::Running => Response::new(
+            StatusCode::NotModified,
+            Some("Pomodoro counter already running.".to_owned()),
+        ),
+        _ => Response::new(StatusCode::Conflict, Some("Nothing to resume.".to_owned())),
+    }
+}
+
+/// Adds `arg1` seconds to the current pomodoro, counting down again when it
+/// was in overtime or awaiting acknowledgement.
+pub fn extend(request: &Request) -> Response {
+    let seconds = match request.arg1().map(|a| a.parse::<u32>()) {
+        Some(Ok(s)) if s > 0 => s,
+        _ => {
+            return Response::new(
+                StatusCode::BadRequest,
+                Some("Invalid time format.".to_owned()),
+            )
+        }
+    };
+
+    let mut rt = REMAINING_TIME.write().unwrap();
+    let mut cs = COUNTER_STATE.write().unwrap();
+    let mut session = SESSION.write().unwrap();
+
+    let session = match (&*cs, session.as_mut()) {
+        (
+            CounterState::Running
+            | CounterState::Paused
+            | CounterState::Overtime
+            | CounterState::AwaitingAck,
+            Some(session),
+        ) if matches!(session.kind, Kind::Pomodoro) => session,
         _ => {
             return Response::new(
                 StatusCode::Conflict,
-                Some("nothing to pause/resume.".to_owned()),
+                Some("No pomodoro to extend.".to_owned()),
+            )
+        }
+    };
+
+    let (duration, remaining) = match *cs {
+        CounterState::Overtime | CounterState::AwaitingAck => {
+            (session.duration.checked_add(seconds), Some(seconds))
+        }
+        _ => (
+            session.duration.checked_add(seconds),
+            rt.checked_add(seconds),
+        ),
+    };
+
+    let (duration, remaining) = match (duration, remaining) {
+        (Some(duration), Some(remaining)) => (duration, remaining),
+        _ => {
+            return Response::new(
+                StatusCode::BadRequest,
+                Some("Extension too long.".to_owned()),
             )
         }
+    };
+
+    if let Err(e) = session.options.bounds.check(duration) {
+        return Response::new(StatusCode::BadRequest, Some(e.to_string()));
     }
+
+    // a finished pomodoro counts down again, reminding anew once it ends
+    if let CounterState::Overtime | CounterState::AwaitingAck = *cs {
+        *cs = CounterState::Running;
+        session.reminders = 0;
+    }
+
+    *rt = remaining;
+    session.duration = duration;
+
+    Response::new(
+        StatusCode::Ok,
+        Some(format!(
+            "Pomodoro extended by {}, {} left.",
+            Time::get_duration_from_seconds(&seconds),
+            Time::get_duration_from_seconds(&rt)
+        )),
+    )
 }
 
-pub fn is_counter_running() -> Response {
+/// Logs an interruption of the current session, pausing it when asked to.
+pub fn interrupt(request: &Request) -> Response {
+    let source = match request.arg1() {
+        Some("external") => Source::External,
+        Some("internal") => Source::Internal,
+        _ => {
+            return Response::new(
+                StatusCode::BadRequest,
+                Some("Invalid interruption source.".to_owned()),
+            )
+        }
+    };
+
+    let (count, paused, mut env) = {
+        let mut cs = COUNTER_STATE.write().unwrap();
+        let mut session = SESSION.write().unwrap();
+
+        let session = match (&*cs, session.as_mut()) {
+            (CounterState::Pristine, _) | (_, None) => {
+                return Response::new(
+                    StatusCode::Conflict,
+                    Some("No session to interrupt.".to_owned()),
+                )
+            }
+            (_, Some(session)) => session,
+        };
+
+        session.interruptions.push(Interruption {
+            at: Local::now(),
+            source,
+            note: request.header("note").map(|h| h.to_owned()),
+        });
+
+        let paused =
+            request.header("pause") == Some("true") && matches!(*cs, CounterState::Running);
+
+        if paused {
+            set_pause_limits(None, None);
+            *cs = CounterState::Paused;
+        }
+
+        (session.interruptions.len(), paused, session.labels.env())
+    };
+
+    env.push(("PDORO_INTERRUPTION", source.to_string()));
+    run_hook("interruption", &env);
+
+    let msg = match paused {
+        true => format!("Interruption {} logged, pomodoro counter paused.", count),
+        false => format!("Interruption {} logged.", count),
+    };
+
+    Response::new(StatusCode::Created, Some(msg))
+}
+
+/// Adds a note to the current session, or to the last recorded one when idle.
+pub fn note(request: &Request) -> Response {
+    let text = match request.header("text") {
+        Some(text) if !text.is_empty() => text.to_owned(),
+        _ => return Response::new(StatusCode::BadRequest, Some("Missing note.".to_owned())),
+    };
+
+    {
+        let cs = COUNTER_STATE.read().unwrap();
+        let mut session = SESSION.write().unwrap();
+
+        if let (false, Some(session)) = (matches!(*cs, CounterState::Pristine), session.as_mut()) {
+            session.notes.push(text);
+            return Response::new(
+                StatusCode::Created,
+                Some("Note added to the current session.".to_owned()),
+            );
+        }
+    }
+
+    match history::amend_last(&Config::global().history_path(), |record| {
+        record.notes.push(text)
+    }) {
+        Ok(true) => Response::new(
+            StatusCode::Created,
+            Some("Note added to the last session.".to_owned()),
+        ),
+        Ok(false) => Response::new(
+            StatusCode::Conflict,
+            Some("No session to add a note to.".to_owned()),
+        ),
+        Err(e) => Response::new(StatusCode::InternalServerError, Some(e)),
+    }
+}
+
+/// Adds the json `records`, one per line, to history through the server so
+/// they do not race its own writes.
+pub fn import(request: &Request) -> Response {
+    let records = match request.header("records").map(|records| {
+        records
+            .lines()
+            .map(serde_json::from_str)
+            .collect::<Result<Vec<Record>, _>>()
+    }) {
+        Some(Ok(records)) => records,
+        _ => return Response::new(StatusCode::BadRequest, Some("Invalid records.".to_owned())),
+    };
+
+    match history::append_missing(&Config::global().history_path(), &records) {
+        Ok(appended) => {
+            progress::reset();
+            Response::new(StatusCode::Created, Some(appended.to_string()))
+        }
+        Err(e) => Response::new(StatusCode::InternalServerError, Some(e)),
+    }
+}
+
+pub fn acknowledge() -> Response {
+    let msg = {
+        let mut cs = COUNTER_STATE.write().unwrap();
+        let msg = match *cs {
+            CounterState::Overtime => "Overtime ended.",
+            CounterState::AwaitingAck => "Pomodoro acknowledged.",
+            _ => {
+                return Response::new(
+                    StatusCode::Conflict,
+                    Some("Nothing to acknowledge.".to_owned()),
+                )
+            }
+        };
+
+        end_finished(&mut cs);
+        msg
+    };
+
+    advance_queue();
+
+    Response::new(StatusCode::Ok, Some(msg.to_owned()))
+}
+
+pub fn goal() -> Response {
+    let progress = progress::today();
+
+    match (progress.target.is_empty(), progress.is_reached()) {
+        (true, _) => Response::new(StatusCode::Ok, Some("No daily goal set.".to_owned())),
+        (false, true) => Response::new(
+            StatusCode::Ok,
+            Some(format!("{} today, goal reached.", progress)),
+        ),
+        (false, false) => Response::new(StatusCode::Ok, Some(format!("{} today.", progress))),
+    }
+}
+
+/// Whether a session is going, or with `awaiting-ack` whether a finished
+/// pomodoro waits for acknowledgement.
+pub fn is_counter_running(request: &Request) -> Response {
     let state = COUNTER_STATE.read().unwrap();
 
-    match *state {
-        CounterState::Pristine => Response::new(StatusCode::Continue, Some("false".to_owned())),
-        _ => Response::new(StatusCode::Processing, Some("true".to_owned())),
+    let running = match request.arg1() {
+        Some("awaiting-ack") => matches!(*state, CounterState::AwaitingAck),
+        _ => !matches!(*state, CounterState::Pristine),
+    };
+
+    match running {
+        false => Response::new(StatusCode::Continue, Some("false".to_owned())),
+        true => Response::new(StatusCode::Processing, Some("true".to_owned())),
     }
 }
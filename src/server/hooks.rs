@@ -0,0 +1,31 @@
+use std::env;
+use std::process::Command;
+use std::thread;
+
+use super::controllers::parse_callback_with_args;
+
+static HOOK_VAR: &str = "PDORO_HOOK";
+
+/// Runs the program set in `PDORO_HOOK` (if any) with the event name as its
+/// last argument and `env` as extra environment variables.
+pub fn run_hook(event: &str, env: &[(&str, String)]) {
+    let hook = match env::var(HOOK_VAR) {
+        Ok(hook) if !hook.is_empty() => hook,
+        _ => return,
+    };
+
+    let (program, mut args) = parse_callback_with_args(&hook);
+    args.push(event.to_owned());
+
+    let mut command = Command::new(program);
+    command.args(args).envs(env.iter().map(|(k, v)| (k, v)));
+
+    thread::spawn(move || match command.spawn() {
+        Ok(mut child) => {
+            if let Err(e) = child.wait() {
+                println!("Failed to wait for hook: {}", e)
+            }
+        }
+        Err(e) => println!("Failed to run hook: {}", e),
+    });
+}
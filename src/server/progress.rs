@@ -0,0 +1,60 @@
+use std::sync::RwLock;
+
+use chrono::{Local, NaiveDate};
+
+use crate::config::Config;
+use crate::goals::Progress;
+use crate::history::{self, Record};
+
+use super::hooks::run_hook;
+
+static TODAY: RwLock<Option<(NaiveDate, Progress)>> = RwLock::new(None);
+
+/// Progress of the current day, read from history again once the day changed.
+pub fn today() -> Progress {
+    let config = Config::global();
+    let day = config.day_of(&Local::now());
+    let mut today = TODAY.write().unwrap();
+
+    match *today {
+        Some((cached_day, progress)) if cached_day == day => progress,
+        _ => {
+            let records = history::read(&config.history_path()).unwrap_or_else(|e| {
+                println!("{}", e);
+                vec![]
+            });
+
+            let progress = config.progress(&records, day);
+            *today = Some((day, progress));
+            progress
+        }
+    }
+}
+
+/// Counts a session about to be recorded, firing `goal-reached` when it
+/// meets the goal of the day.
+pub fn add(record: &Record) {
+    let config = Config::global();
+    let day = config.day_of(&Local::now());
+
+    // a session started before the day boundary counts towards the day before
+    if config.day_of(&record.started_at) != day {
+        return;
+    }
+
+    let mut progress = today();
+    let was_reached = progress.is_reached();
+
+    progress.add(record);
+    *TODAY.write().unwrap() = Some((day, progress));
+
+    if !was_reached && progress.is_reached() {
+        run_hook("goal-reached", &[("PDORO_GOAL", progress.to_string())]);
+    }
+}
+
+/// Makes `today` read history again, after sessions were added to it
+/// other than by `add`.
+pub fn reset() {
+    *TODAY.write().unwrap() = None;
+}
@@ -0,0 +1,227 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::fs;
+use std::sync::RwLock;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::labels::Labels;
+use crate::phase::Phase;
+use crate::time::Time;
+
+use super::request::Request;
+use super::response::Response;
+use super::status_code::StatusCode;
+
+/// A pomodoro waiting for its turn, stored as json in the queue file.
+#[derive(Serialize, Deserialize)]
+pub struct Entry {
+    pub seconds: u32,
+    #[serde(flatten)]
+    pub labels: Labels,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub callback: Option<String>,
+    /// name of the schedule it is a run of
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schedule: Option<String>,
+}
+
+impl Display for Entry {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self.labels.is_empty() {
+            false => write!(
+                f,
+                "{} {}",
+                Time::get_duration_from_seconds(&self.seconds),
+                self.labels
+            ),
+            true => write!(f, "{}", Time::get_duration_from_seconds(&self.seconds)),
+        }
+    }
+}
+
+static QUEUE: RwLock<Vec<Entry>> = RwLock::new(Vec::new());
+
+/// Restores the queue saved by a previous server.
+pub fn load() {
+    let path = Config::global().queue_path();
+
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(_) => return,
+    };
+
+    match serde_json::from_str(&content) {
+        Ok(entries) => *QUEUE.write().unwrap() = entries,
+        Err(e) => println!("Failed to load queue {}: {}", path.display(), e),
+    }
+}
+
+fn save(queue: &[Entry]) {
+    let path = Config::global().queue_path();
+
+    let result = serde_json::to_string(queue)
+        .map_err(|e| e.to_string())
+        .and_then(|json| fs::write(&path, json).map_err(|e| e.to_string()));
+
+    if let Err(e) = result {
+        println!("Failed to save queue {}: {}", path.display(), e)
+    }
+}
+
+pub fn pop() -> Option<Entry> {
+    let mut queue = QUEUE.write().unwrap();
+
+    match queue.is_empty() {
+        true => None,
+        false => {
+            let entry = queue.remove(0);
+            save(&queue);
+            Some(entry)
+        }
+    }
+}
+
+/// Puts a schedule run first, unless a run of the same schedule already
+/// waits in the queue. Returns whether it was queued.
+pub fn push_front(entry: Entry) -> bool {
+    let mut queue = QUEUE.write().unwrap();
+
+    if entry.schedule.is_some() && queue.iter().any(|queued| queued.schedule == entry.schedule) {
+        return false;
+    }
+
+    queue.insert(0, entry);
+    save(&queue);
+    true
+}
+
+/// Whether the pomodoro that starts next is a schedule run.
+pub fn first_is_scheduled() -> bool {
+    QUEUE
+        .read()
+        .unwrap()
+        .first()
+        .is_some_and(|entry| entry.schedule.is_some())
+}
+
+/// The pomodoro that starts next, as listed.
+pub fn first() -> Option<String> {
+    QUEUE.read().unwrap().first().map(|entry| entry.to_string())
+}
+
+pub fn is_empty() -> bool {
+    QUEUE.read().unwrap().is_empty()
+}
+
+pub fn add(request: &Request) -> Response {
+    let seconds = match request.arg1().map(|a| a.parse::<u32>()) {
+        Some(Ok(s)) if s > 0 => s,
+        _ => {
+            return Response::new(
+                StatusCode::BadRequest,
+                Some("Invalid time format.".to_owned()),
+            )
+        }
+    };
+
+    let bounds = Config::global()
+        .profile(request.header("profile"))
+        .and_then(|p| {
+            p.bounds(Phase::Work)
+                .check(seconds)
+                .map_err(|e| e.to_string())
+        });
+
+    if let Err(e) = bounds {
+        return Response::new(StatusCode::BadRequest, Some(e));
+    }
+
+    let entry = Entry {
+        seconds,
+        labels: Labels::new(request.header("task"), request.header("tags")),
+        profile: request.header("profile").map(|h| h.to_owned()),
+        callback: request.header("callback").map(|h| h.to_owned()),
+        schedule: None,
+    };
+
+    let mut queue = QUEUE.write().unwrap();
+    let msg = format!("Queued {}. {}", queue.len() + 1, entry);
+
+    queue.push(entry);
+    save(&queue);
+
+    Response::new(StatusCode::Created, Some(msg))
+}
+
+pub fn list() -> Response {
+    let queue = QUEUE.read().unwrap();
+
+    if queue.is_empty() {
+        return Response::new(StatusCode::Ok, Some("Queue is empty.".to_owned()));
+    }
+
+    let lines: Vec<String> = queue
+        .iter()
+        .enumerate()
+        .map(|(i, entry)| format!("{}. {}", i + 1, entry))
+        .collect();
+
+    Response::new(StatusCode::Ok, Some(lines.join("\n")))
+}
+
+pub fn remove(request: &Request) -> Response {
+    let mut queue = QUEUE.write().unwrap();
+
+    let index = match parse_position(request.arg1(), queue.len()) {
+        Ok(index) => index,
+        Err(res) => return res,
+    };
+
+    let entry = queue.remove(index);
+    save(&queue);
+
+    Response::new(StatusCode::Ok, Some(format!("Removed {}", entry)))
+}
+
+pub fn move_entry(request: &Request) -> Response {
+    let mut queue = QUEUE.write().unwrap();
+
+    let (from, to) = match (
+        parse_position(request.arg1(), queue.len()),
+        parse_position(request.arg2(), queue.len()),
+    ) {
+        (Ok(from), Ok(to)) => (from, to),
+        (Err(res), _) | (_, Err(res)) => return res,
+    };
+
+    let entry = queue.remove(from);
+    let msg = format!("Moved {} to {}.", entry, to + 1);
+
+    queue.insert(to, entry);
+    save(&queue);
+
+    Response::new(StatusCode::Ok, Some(msg))
+}
+
+pub fn clear() -> Response {
+    let mut queue = QUEUE.write().unwrap();
+
+    queue.clear();
+    save(&queue);
+
+    Response::new(StatusCode::Ok, Some("Queue cleared.".to_owned()))
+}
+
+/// Converts a 1-based position to an index into a queue of `len` entries.
+fn parse_position(arg: Option<&str>, len: usize) -> Result<usize, Response> {
+    match arg.map(|a| a.parse::<usize>()) {
+        Some(Ok(position)) if position >= 1 && position <= len => Ok(position - 1),
+        _ => Err(Response::new(
+            StatusCode::BadRequest,
+            Some(format!("Invalid position, queue has {} entries.", len)),
+        )),
+    }
+}
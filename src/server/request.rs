@@ -2,11 +2,14 @@ use std::convert::TryFrom;
 use std::fmt::{Display, Formatter, Result as FmtResult};
 use std::str;
 
+use crate::header::unescape;
+
 #[derive(Debug)]
 pub struct Request<'buf> {
     path: &'buf str,
     arg1: Option<&'buf str>,
     arg2: Option<&'buf str>,
+    headers: Vec<(&'buf str, String)>,
 }
 
 impl<'buf> Display for Request<'buf> {
@@ -30,7 +33,7 @@ impl<'buf> Request<'buf> {
 
 impl<'buf> Request<'buf> {
     pub fn path(&self) -> &str {
-        &self.path
+        self.path
     }
 
     pub fn arg1(&self) -> Option<&str> {
@@ -40,6 +43,13 @@ impl<'buf> Request<'buf> {
     pub fn arg2(&self) -> Option<&str> {
         self.arg2
     }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| value.as_str())
+    }
 }
 
 impl<'buf> TryFrom<&'buf [u8]> for Request<'buf> {
@@ -53,15 +63,31 @@ impl<'buf> TryFrom<&'buf [u8]> for Request<'buf> {
             None => Err("Request not terminated by ';' char".to_owned())?,
         };
 
-        let (path, arg1, arg2) = parse_request(&request);
+        let mut lines = request.split('\n');
+        let (path, arg1, arg2) = parse_request(lines.next().unwrap_or_default());
+        let headers = lines.map(parse_header).collect::<Result<_, _>>()?;
 
         match path {
-            Some(path) => Ok(Self { path, arg1, arg2 }),
+            Some(path) => Ok(Self {
+                path,
+                arg1,
+                arg2,
+                headers,
+            }),
             None => Err("Invalid request line".to_owned()),
         }
     }
 }
 
+/// Lines after the request line are `key: value` headers, with values
+/// escaped by `header::escape`.
+fn parse_header(line: &str) -> Result<(&str, String), String> {
+    match line.split_once(':') {
+        Some((key, value)) => Ok((key.trim(), unescape(value.trim()))),
+        None => Err(format!("Invalid header line: {}", line)),
+    }
+}
+
 fn parse_request(request: &str) -> (Option<&str>, Option<&str>, Option<&str>) {
     let mut parts = request.splitn(3, ' ');
 
@@ -13,6 +13,14 @@ impl Response {
         Self { status_code, body }
     }
 
+    pub fn status_code(&self) -> StatusCode {
+        self.status_code
+    }
+
+    pub fn body(&self) -> Option<&str> {
+        self.body.as_deref()
+    }
+
     pub fn send(&self, stream: &mut impl Write) -> IoResult<()> {
         match &self.body {
             Some(body) => write!(stream, "{} {};", self.status_code, body),
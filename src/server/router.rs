@@ -1,18 +1,37 @@
 use super::controllers::{
-    halt_counter, health_check, is_counter_running, not_found, pause_resume_counter,
-    remaining_pomodoro, start_pomodoro,
+    acknowledge, extend, goal, halt_counter, health_check, import, interrupt, is_counter_running,
+    not_found, note, pause_counter, pause_resume_counter, remaining_pomodoro, resume_counter,
+    start_next_queued, start_pomodoro, start_stopwatch,
 };
+use super::queue;
 use super::request::Request;
 use super::response::Response;
+use super::scheduler;
 
 pub fn router(request: &Request) -> Response {
     match request.path() {
         "healthcheck" => health_check(),
         "start" => start_pomodoro(request),
+        "stopwatch" => start_stopwatch(request),
         "halt-counter" => halt_counter(),
         "remaining" => remaining_pomodoro(),
-        "is-counter-running" => is_counter_running(),
+        "is-counter-running" => is_counter_running(request),
         "pause-resume-counter" => pause_resume_counter(),
+        "pause" => pause_counter(request),
+        "resume" => resume_counter(),
+        "ack" => acknowledge(),
+        "extend" => extend(request),
+        "interrupt" => interrupt(request),
+        "note" => note(request),
+        "import" => import(request),
+        "goal" => goal(),
+        "queue-add" => queue::add(request),
+        "queue-list" => queue::list(),
+        "queue-remove" => queue::remove(request),
+        "queue-move" => queue::move_entry(request),
+        "queue-clear" => queue::clear(),
+        "queue-next" => start_next_queued(request),
+        "schedule-list" => scheduler::list(),
         _ => not_found(),
     }
 }
@@ -0,0 +1,151 @@
+use std::fs;
+use std::thread;
+
+use chrono::{Duration, Local, NaiveDateTime, Timelike};
+
+use crate::config::{Config, Missed, Schedule};
+use crate::time::Time;
+use crate::utils::sleep;
+
+use super::controllers::start_scheduled;
+use super::hooks::run_hook;
+use super::queue::Entry;
+use super::response::Response;
+use super::status_code::StatusCode;
+
+const STATE_FORMAT: &str = "%Y-%m-%dT%H:%M";
+
+/// Starts the configured schedules once a minute passes one of their runs.
+/// Runs missed while the server was down are handled by each schedule's
+/// `missed` policy.
+pub fn spawn() {
+    let schedules = Config::global().schedules();
+
+    if schedules.is_empty() {
+        return;
+    }
+
+    thread::spawn(move || {
+        // runs of the current minute are not missed, the loop below starts them
+        let mut last_checked = current_minute() - Duration::minutes(1);
+
+        if let Some(stopped_at) = load_last_checked() {
+            for schedule in schedules {
+                if runs_between(schedule, stopped_at, last_checked) {
+                    handle_missed(schedule);
+                }
+            }
+        }
+
+        loop {
+            let now = current_minute();
+
+            if now > last_checked {
+                for schedule in schedules {
+                    if runs_between(schedule, last_checked, now) {
+                        start(schedule);
+                    }
+                }
+
+                last_checked = now;
+                save_last_checked(now);
+            }
+
+            sleep(1);
+        }
+    });
+}
+
+fn current_minute() -> NaiveDateTime {
+    let now = Local::now().naive_local();
+    now.with_second(0)
+        .and_then(|t| t.with_nanosecond(0))
+        .unwrap_or(now)
+}
+
+/// Whether `schedule` runs after `after` and no later than `until`.
+fn runs_between(schedule: &Schedule, after: NaiveDateTime, until: NaiveDateTime) -> bool {
+    schedule.next_after(after).is_some_and(|run| run <= until)
+}
+
+fn start(schedule: &Schedule) {
+    let entry = Entry {
+        seconds: schedule.seconds,
+        labels: schedule.labels.clone(),
+        profile: schedule.profile.clone(),
+        callback: schedule.callback.clone(),
+        schedule: Some(schedule.name.clone()),
+    };
+
+    start_scheduled(&schedule.name, entry);
+}
+
+fn handle_missed(schedule: &Schedule) {
+    match schedule.missed {
+        Missed::Skip => {
+            println!("Skipped missed schedule {}", schedule.name);
+            run_hook(
+                "schedule-missed",
+                &[("PDORO_SCHEDULE", schedule.name.clone())],
+            );
+        }
+        Missed::RunOnce => start(schedule),
+    }
+}
+
+fn load_last_checked() -> Option<NaiveDateTime> {
+    let content = fs::read_to_string(Config::global().schedule_state_path()).ok()?;
+    NaiveDateTime::parse_from_str(content.trim(), STATE_FORMAT).ok()
+}
+
+fn save_last_checked(time: NaiveDateTime) {
+    let path = Config::global().schedule_state_path();
+
+    if let Err(e) = fs::write(&path, time.format(STATE_FORMAT).to_string()) {
+        println!("Failed to save schedule state {}: {}", path.display(), e)
+    }
+}
+
+/// Lists the next run of every schedule, soonest first.
+pub fn list() -> Response {
+    let now = Local::now().naive_local();
+
+    let mut runs: Vec<(Option<NaiveDateTime>, &Schedule)> = Config::global()
+        .schedules()
+        .iter()
+        .map(|schedule| (schedule.next_after(now), schedule))
+        .collect();
+
+    if runs.is_empty() {
+        return Response::new(StatusCode::Ok, Some("No schedules.".to_owned()));
+    }
+
+    // schedules without upcoming runs go last
+    runs.sort_by_key(|(run, _)| (run.is_none(), *run));
+
+    let lines: Vec<String> = runs
+        .iter()
+        .map(|(run, schedule)| {
+            let run = match run {
+                Some(run) => run.format("%a %Y-%m-%d %H:%M").to_string(),
+                None => "no upcoming run".to_owned(),
+            };
+
+            let mut line = format!(
+                "{}  {}  {}",
+                run,
+                schedule.name,
+                Time::get_duration_from_seconds(&schedule.seconds)
+            );
+
+            if !schedule.labels.is_empty() {
+                line.push(' ');
+                line.push_str(schedule.labels.to_string().as_str());
+            }
+
+            line
+        })
+        .collect();
+
+    Response::new(StatusCode::Ok, Some(lines.join("\n")))
+}
@@ -0,0 +1,30 @@
+use std::fs;
+use std::sync::Mutex;
+
+use crate::config::Config;
+
+use super::controllers::remaining_pomodoro;
+
+/// Keeps two writers from saving their snapshots out of order.
+static WRITING: Mutex<()> = Mutex::new(());
+
+/// Saves the `remaining` response to the status file for `pdoro prompt`.
+/// It is written next to it and renamed over it, so a reader never sees
+/// half of it.
+pub fn update() {
+    let _writing = WRITING.lock().unwrap();
+
+    let mut content = vec![];
+
+    if let Err(e) = remaining_pomodoro().send(&mut content) {
+        return println!("Failed to read status: {}", e);
+    }
+
+    let path = Config::global().status_file_path();
+    let mut temporary = path.clone().into_os_string();
+    temporary.push(".tmp");
+
+    if let Err(e) = fs::write(&temporary, content).and_then(|_| fs::rename(&temporary, &path)) {
+        println!("Failed to save status {}: {}", path.display(), e)
+    }
+}
@@ -1,12 +1,28 @@
 use super::request::Request;
 use super::response::Response;
 use super::router::router;
+use super::status_file;
 use super::Handler;
 
 pub struct TCPHandler;
 
 impl Handler for TCPHandler {
     fn handle_request(&self, request: &Request) -> Response {
-        router(request)
+        let response = router(request);
+
+        // anything but a read may have changed the timer or the queue
+        if !matches!(
+            request.path(),
+            "healthcheck"
+                | "remaining"
+                | "is-counter-running"
+                | "goal"
+                | "queue-list"
+                | "schedule-list"
+        ) {
+            status_file::update();
+        }
+
+        response
     }
 }
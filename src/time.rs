@@ -1,69 +1,348 @@
+use chrono::{Duration, Local, NaiveTime, TimeZone};
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+#[derive(Clone, Copy, PartialEq, PartialOrd)]
 pub enum TimeFormat {
-    Hours,
-    Minutes,
     Seconds,
+    Minutes,
+    Hours,
+}
+
+impl TimeFormat {
+    fn from_char(c: char) -> Option<Self> {
+        match c {
+            'h' | 'H' => Some(Self::Hours),
+            'm' | 'M' => Some(Self::Minutes),
+            's' | 'S' => Some(Self::Seconds),
+            _ => None,
+        }
+    }
+
+    /// Unit assumed for a number written without one, e.g. the `30` in `1h30`.
+    fn next_smaller(last: Option<Self>) -> Option<Self> {
+        match last {
+            None => Some(Self::Minutes),
+            Some(Self::Hours) => Some(Self::Minutes),
+            Some(Self::Minutes) => Some(Self::Seconds),
+            Some(Self::Seconds) => None,
+        }
+    }
+
+    fn seconds(&self) -> f64 {
+        match self {
+            Self::Hours => 60.0 * 60.0,
+            Self::Minutes => 60.0,
+            Self::Seconds => 1.0,
+        }
+    }
+}
+
+/// Error pointing at the character of the input that could not be parsed.
+pub struct ParseError {
+    input: String,
+    position: usize,
+    reason: String,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        write!(
+            f,
+            "Invalid time format: {}\n  {}\n  {}^",
+            self.reason,
+            self.input,
+            " ".repeat(self.position)
+        )
+    }
+}
+
+type ParseResult<T> = Result<T, (usize, String)>;
+
+pub enum TimeError {
+    Parse(ParseError),
+    BelowMin { seconds: u32, min: u32 },
+    AboveMax { seconds: u32, max: u32 },
+}
+
+impl Display for TimeError {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self {
+            Self::Parse(e) => write!(f, "{}", e),
+            Self::BelowMin { seconds, min } => write!(
+                f,
+                "Invalid length: {} is below the minimum of {}",
+                Time::get_duration_from_seconds(seconds),
+                Time::get_duration_from_seconds(min)
+            ),
+            Self::AboveMax { seconds, max } => write!(
+                f,
+                "Invalid length: {} is above the maximum of {}",
+                Time::get_duration_from_seconds(seconds),
+                Time::get_duration_from_seconds(max)
+            ),
+        }
+    }
+}
+
+/// Inclusive range of accepted durations, in seconds.
+#[derive(Clone, Copy)]
+pub struct Bounds {
+    pub min: u32,
+    pub max: u32,
+}
+
+impl Default for Bounds {
+    fn default() -> Self {
+        Self {
+            min: 1,
+            // below 10h
+            max: 60 * 60 * 10 - 1,
+        }
+    }
+}
+
+impl Bounds {
+    pub fn check(&self, seconds: u32) -> Result<(), TimeError> {
+        match self {
+            Bounds { min, .. } if seconds < *min => Err(TimeError::BelowMin { seconds, min: *min }),
+            Bounds { max, .. } if seconds > *max => Err(TimeError::AboveMax { seconds, max: *max }),
+            _ => Ok(()),
+        }
+    }
 }
 
 pub struct Time {
-    pub format: TimeFormat,
-    pub value: u32,
+    seconds: u32,
 }
 
 impl Time {
-    pub fn new(input: &str) -> Result<Self, String> {
-        let time = Self::input_to_time(input)?;
+    /// Accepts compound units (`1h30m`, `25m5s`, `1.5h`), bare minutes (`90`),
+    /// clock notation (`25:00`, `1:30:00`) and ISO-8601 (`PT25M`).
+    pub fn new(input: &str, bounds: &Bounds) -> Result<Self, TimeError> {
+        let time = Self::parse(input)?;
+
+        time.check_len(bounds)?;
+        Ok(time)
+    }
+
+    /// Parses `input` without checking its length.
+    pub fn parse(input: &str) -> Result<Self, TimeError> {
+        Self::input_to_time(input).map_err(TimeError::Parse)
+    }
+
+    /// Duration from now until the next local wall-clock `end_time` (HH:MM or HH:MM:SS).
+    pub fn until(end_time: &str, roll_over: bool, bounds: &Bounds) -> Result<Self, String> {
+        let end_time = NaiveTime::parse_from_str(end_time, "%H:%M")
+            .or_else(|_| NaiveTime::parse_from_str(end_time, "%H:%M:%S"))
+            .map_err(|_| "Invalid end time format: expected HH:MM".to_string())?;
 
-        match Self::is_valid_len(&time) {
-            true => Ok(time),
-            false => Err("Invalid length: input must be in range (1s < input < 10h)".to_string()),
+        let now = Local::now();
+        let mut end = now.date_naive().and_time(end_time);
+
+        if end <= now.naive_local() {
+            if !roll_over {
+                return Err(
+                    "End time already passed today, use --roll-over to end tomorrow".to_string(),
+                );
+            }
+
+            end += Duration::days(1);
         }
+
+        let end = Local
+            .from_local_datetime(&end)
+            .earliest()
+            .ok_or("End time does not exist in local time zone".to_string())?;
+
+        let time = Self {
+            seconds: (end - now).num_seconds() as u32,
+        };
+
+        time.check_len(bounds).map_err(|e| e.to_string())?;
+        Ok(time)
     }
 
-    fn input_to_time(input: &str) -> Result<Self, String> {
-        let mut value = String::new();
-        let mut has_num = false;
+    fn input_to_time(input: &str) -> Result<Self, ParseError> {
+        let chars: Vec<char> = input.chars().collect();
+
+        let seconds = match chars.first() {
+            None => Err((0, "empty input".to_string())),
+            Some('P' | 'p') => Self::parse_iso(&chars),
+            Some(_) if chars.contains(&':') => Self::parse_clock(&chars),
+            Some(_) => Self::parse_units(&chars, 0, false),
+        };
+
+        let to_error = |(position, reason)| ParseError {
+            input: input.to_owned(),
+            position,
+            reason,
+        };
 
-        for c in input.chars() {
+        match seconds.map_err(to_error)?.round() {
+            s if s > u32::MAX as f64 => Err(to_error((0, "duration too long".to_string()))),
+            s => Ok(Self { seconds: s as u32 }),
+        }
+    }
+
+    /// `PT1H30M`, units are mandatory and only the time part is supported.
+    fn parse_iso(chars: &[char]) -> ParseResult<f64> {
+        match chars.get(1) {
+            Some('T' | 't') => Self::parse_units(chars, 2, true),
+            Some(c) => Err((1, format!("unexpected '{}', expected 'T'", c))),
+            None => Err((1, "expected 'T'".to_string())),
+        }
+    }
+
+    /// `MM:SS` or `HH:MM:SS`.
+    fn parse_clock(chars: &[char]) -> ParseResult<f64> {
+        let mut parts = vec![];
+        let mut start = 0;
+
+        for (i, c) in chars.iter().enumerate() {
             match c {
-                c if c.is_alphabetic() && !has_num => return Err("Invalid time format".to_string()),
-                c if c.is_numeric() => {
-                    has_num = true;
-                    value.push(c)
+                ':' => {
+                    parts.push((start, i));
+                    start = i + 1;
                 }
-                'h' | 'H' => return Ok(Self::parsed(TimeFormat::Hours, &value)),
-                'm' | 'M' => return Ok(Self::parsed(TimeFormat::Minutes, &value)),
-                's' | 'S' => return Ok(Self::parsed(TimeFormat::Seconds, &value)),
-                _ => return Err("Invalid time format".to_string()),
+                c if c.is_ascii_digit() => {}
+                c => return Err((i, format!("unexpected '{}', expected a digit or ':'", c))),
             }
         }
+        parts.push((start, chars.len()));
 
-        Err("Invalid time format".to_string())
+        if parts.len() > 3 {
+            let (third_start, _) = parts[3];
+            return Err((third_start - 1, "too many ':' separators".to_string()));
+        }
+
+        let mut seconds = 0.0;
+
+        for (index, (start, end)) in parts.iter().enumerate() {
+            if start == end {
+                return Err((*start, "expected a number".to_string()));
+            }
+
+            let value: u32 = chars[*start..*end]
+                .iter()
+                .collect::<String>()
+                .parse()
+                .map_err(|_| (*start, "number too large".to_string()))?;
+
+            if index > 0 && value >= 60 {
+                return Err((*start, "must be less than 60".to_string()));
+            }
+
+            seconds = seconds * 60.0 + value as f64;
+        }
+
+        Ok(seconds)
     }
 
-    fn is_valid_len(time: &Self) -> bool {
-        if time.value == 0 {
-            return false;
+    /// Sequence of `<number><unit>` in descending unit order, e.g. `1h30m15s`.
+    fn parse_units(chars: &[char], start: usize, require_units: bool) -> ParseResult<f64> {
+        let mut seconds = 0.0;
+        let mut last_unit = None;
+        let mut pos = start;
+
+        if pos == chars.len() {
+            return Err((pos, "expected a number".to_string()));
         }
 
-        match time.format {
-            TimeFormat::Hours => time.value < 10,
-            TimeFormat::Minutes => time.value < 60 * 10,
-            TimeFormat::Seconds => time.value < 60 * 60 * 10,
+        while pos < chars.len() {
+            let number_start = pos;
+            let (value, has_fraction) = Self::parse_number(chars, &mut pos)?;
+
+            let unit = match chars.get(pos) {
+                Some(&c) => match TimeFormat::from_char(c) {
+                    Some(unit) => unit,
+                    None => return Err((pos, format!("unexpected '{}', expected h, m or s", c))),
+                },
+                None if require_units => return Err((pos, "expected a unit".to_string())),
+                None => match TimeFormat::next_smaller(last_unit) {
+                    Some(unit) => unit,
+                    None => return Err((pos, "expected a unit".to_string())),
+                },
+            };
+
+            if last_unit.is_some_and(|last| unit >= last) {
+                return Err((pos, "units must go from largest to smallest".to_string()));
+            }
+
+            // `1h60m` is written `2h`, as in clock notation
+            if last_unit.is_some() && value >= 60.0 {
+                return Err((number_start, "must be less than 60".to_string()));
+            }
+
+            pos += 1;
+
+            if has_fraction && pos < chars.len() {
+                return Err((
+                    number_start,
+                    "only the last number can have decimals".to_string(),
+                ));
+            }
+
+            seconds += value * unit.seconds();
+            last_unit = Some(unit);
         }
+
+        Ok(seconds)
     }
 
-    pub fn parsed(format: TimeFormat, value: &str) -> Self {
-        Self {
-            format,
-            value: value.parse::<u32>().expect("Failed to parse time"),
+    fn parse_number(chars: &[char], pos: &mut usize) -> ParseResult<(f64, bool)> {
+        let start = *pos;
+        let mut has_fraction = false;
+
+        while let Some(&c) = chars.get(*pos) {
+            match c {
+                '.' if !has_fraction => has_fraction = true,
+                c if c.is_ascii_digit() => {}
+                _ => break,
+            }
+            *pos += 1;
         }
+
+        let number: String = chars[start..*pos].iter().collect();
+
+        match number.parse::<f64>() {
+            Ok(value) if number.starts_with(|c: char| c.is_ascii_digit()) => {
+                Ok((value, has_fraction))
+            }
+            _ => match chars.get(start) {
+                Some(c) if !c.is_ascii_digit() && *c != '.' => {
+                    Err((start, format!("unexpected '{}', expected a number", c)))
+                }
+                _ => Err((start, "expected a number".to_string())),
+            },
+        }
+    }
+
+    fn check_len(&self, bounds: &Bounds) -> Result<(), TimeError> {
+        bounds.check(self.seconds)
     }
 
     pub fn get_seconds(&self) -> u32 {
-        match self.format {
-            TimeFormat::Hours => self.value * 60 * 60,
-            TimeFormat::Minutes => self.value * 60,
-            TimeFormat::Seconds => self.value,
+        self.seconds
+    }
+
+    /// Formats seconds the way they are written as input, e.g. `1h30m`.
+    pub fn get_duration_from_seconds(seconds: &u32) -> String {
+        let parts = [
+            (seconds / 60 / 60, 'h'),
+            (seconds / 60 % 60, 'm'),
+            (seconds % 60, 's'),
+        ];
+
+        match parts
+            .iter()
+            .filter(|(value, _)| *value > 0)
+            .collect::<Vec<_>>()
+        {
+            non_zero if non_zero.is_empty() => "0s".to_string(),
+            non_zero => non_zero
+                .iter()
+                .map(|(v, unit)| format!("{}{}", v, unit))
+                .collect(),
         }
     }
 
@@ -73,9 +352,141 @@ impl Time {
         let seconds = seconds % 60;
 
         match (hours, minutes, seconds) {
-            (0, 0, _) => return format!("{:02}", seconds),
-            (0, _, _) => return format!("{:02}:{:02}", minutes, seconds),
-            _ => return format!("{:02}:{:02}:{:02}", hours, minutes, seconds),
+            (0, 0, _) => format!("{:02}", seconds),
+            (0, _, _) => format!("{:02}:{:02}", minutes, seconds),
+            _ => format!("{:02}:{:02}:{:02}", hours, minutes, seconds),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seconds(input: &str) -> Option<u32> {
+        Time::parse(input).ok().map(|time| time.get_seconds())
+    }
+
+    #[test]
+    fn parses_units() {
+        let cases = [
+            ("25m", 25 * 60),
+            ("1h30m", 90 * 60),
+            ("25m5s", 25 * 60 + 5),
+            ("1h30m15s", 5415),
+            ("1H30M", 90 * 60),
+            ("1.5h", 90 * 60),
+            ("0.5m", 30),
+            ("90", 90 * 60),
+            ("1h30", 90 * 60),
+            ("1m30", 90),
+            ("0s", 0),
+            ("59s", 59),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(seconds(input), Some(expected), "{}", input);
+        }
+    }
+
+    #[test]
+    fn parses_clock_and_iso() {
+        let cases = [
+            ("25:00", 25 * 60),
+            ("1:30:00", 90 * 60),
+            ("0:05", 5),
+            ("120:00", 2 * 60 * 60),
+            ("PT25M", 25 * 60),
+            ("pt1h30m", 90 * 60),
+            ("PT1.5H", 90 * 60),
+            ("PT45S", 45),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(seconds(input), Some(expected), "{}", input);
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_input() {
+        let cases = [
+            "",
+            "m",
+            "abc",
+            "25x",
+            "1h60m",
+            "1m60s",
+            "30m1h",
+            "1m1m",
+            "1.5h30m",
+            "1..5h",
+            "1h30s15",
+            "25:60",
+            "1:30:60",
+            "1:2:3:4",
+            ":30",
+            "25:",
+            "25:0a",
+            "P25M",
+            "PT",
+            "PT25",
+            "99999999999h",
+        ];
+
+        for input in cases {
+            assert_eq!(seconds(input), None, "{}", input);
+        }
+    }
+
+    #[test]
+    fn points_at_the_offending_character() {
+        match Time::parse("1h3x") {
+            Err(TimeError::Parse(e)) => assert_eq!(e.position, 3),
+            _ => panic!("expected a parse error"),
+        }
+    }
+
+    #[test]
+    fn checks_bounds() {
+        let bounds = Bounds { min: 60, max: 120 };
+
+        assert!(Time::new("1m", &bounds).is_ok());
+        assert!(Time::new("2m", &bounds).is_ok());
+        assert!(matches!(
+            Time::new("59s", &bounds),
+            Err(TimeError::BelowMin { .. })
+        ));
+        assert!(matches!(
+            Time::new("2m1s", &bounds),
+            Err(TimeError::AboveMax { .. })
+        ));
+        assert!(Time::new("0s", &Bounds::default()).is_err());
+        assert!(Time::new("9h59m59s", &Bounds::default()).is_ok());
+        assert!(Time::new("10h", &Bounds::default()).is_err());
+    }
+
+    #[test]
+    fn formats_durations() {
+        let cases = [(0, "0s"), (59, "59s"), (90 * 60, "1h30m"), (3601, "1h1s")];
+
+        for (seconds, expected) in cases {
+            assert_eq!(Time::get_duration_from_seconds(&seconds), expected);
+        }
+    }
+
+    #[test]
+    fn formats_clocks() {
+        let cases = [
+            (7, "07"),
+            (59, "59"),
+            (60, "01:00"),
+            (25 * 60 + 3, "25:03"),
+            (90 * 60, "01:30:00"),
+            (10 * 3600 - 1, "09:59:59"),
+        ];
+
+        for (seconds, expected) in cases {
+            assert_eq!(Time::get_clock_from_seconds(&seconds), expected);
         }
     }
 }